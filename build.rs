@@ -0,0 +1,173 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+#[path = "build/magic_numbers.rs"]
+mod magic_numbers;
+
+use magic_numbers::{BISHOP_INDEX_BITS, BISHOP_MAGIC_NUMBERS, ROOK_INDEX_BITS, ROOK_MAGIC_NUMBERS};
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+// generates the magic bitboards and one flat attack table for all rook and bishop fields
+// rook fields are stored first, followed by the bishop fields - every magic knows its offset
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=build/magic_numbers.rs");
+
+    let mut attacks: Vec<u64> = Vec::new();
+    let rook_magics = create_magics(&ROOK_MAGIC_NUMBERS, &ROOK_INDEX_BITS, &ROOK_DIRECTIONS, &mut attacks);
+    let bishop_magics = create_magics(&BISHOP_MAGIC_NUMBERS, &BISHOP_INDEX_BITS, &BISHOP_DIRECTIONS, &mut attacks);
+
+    let mut output = String::new();
+    write_magics(&mut output, "ROOK_MAGIC_BITBOARDS", &rook_magics);
+    write_magics(&mut output, "BISHOP_MAGIC_BITBOARDS", &bishop_magics);
+    write_table(&mut output, "SLIDING_ATTACKS", &attacks);
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("magic_tables.rs"), output).unwrap();
+
+    // index is the pext of the blockers with the relevant fields - the table size of a field is 1 << relevant fields
+    if env::var("CARGO_FEATURE_PEXT").is_ok() {
+        let mut attacks: Vec<u64> = Vec::new();
+        let rook_offsets = create_pext_table(&ROOK_DIRECTIONS, &mut attacks);
+        let bishop_offsets = create_pext_table(&BISHOP_DIRECTIONS, &mut attacks);
+
+        let mut output = String::new();
+        writeln!(output, "pub static ROOK_PEXT_OFFSETS: [usize; 64] = {:?};", rook_offsets).unwrap();
+        writeln!(output, "pub static BISHOP_PEXT_OFFSETS: [usize; 64] = {:?};", bishop_offsets).unwrap();
+        write_table(&mut output, "PEXT_ATTACKS", &attacks);
+        fs::write(Path::new(&out_dir).join("pext_tables.rs"), output).unwrap();
+    }
+}
+
+fn write_table(output: &mut String, name: &str, attacks: &[u64]) {
+    writeln!(output, "pub static {}: [u64; {}] = [", name, attacks.len()).unwrap();
+    for chunk in attacks.chunks(8) {
+        let line: Vec<String> = chunk.iter().map(|attack| format!("{:#x}", attack)).collect();
+        writeln!(output, "    {},", line.join(", ")).unwrap();
+    }
+    writeln!(output, "];").unwrap();
+}
+
+fn create_pext_table(directions: &[(i8, i8); 4], attacks: &mut Vec<u64>) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    for position in 0..64 {
+        let relevant_fields = get_relevant_fields(position, directions);
+        let offset = attacks.len();
+        attacks.resize(offset + (1 << relevant_fields.count_ones()), 0);
+        let mut blockers: u64 = 0;
+        loop {
+            attacks[offset + pext(blockers, relevant_fields) as usize] = get_attacks(position, blockers, directions);
+            blockers = blockers.wrapping_sub(relevant_fields) & relevant_fields;
+            if blockers == 0 {
+                break;
+            }
+        }
+        offsets.push(offset);
+    }
+    offsets
+}
+
+// software version of the bmi2 instruction - takes the bits of the mask and packs them to the lowest bits
+fn pext(value: u64, mut mask: u64) -> u64 {
+    let mut result = 0;
+    let mut bit = 0;
+    while mask != 0 {
+        let lowest = mask & mask.wrapping_neg();
+        if value & lowest != 0 {
+            result |= 1 << bit;
+        }
+        bit += 1;
+        mask &= mask - 1;
+    }
+    result
+}
+
+struct Magic {
+    relevant_fields: u64,
+    magic_key: u64,
+    shift: u8,
+    offset: usize,
+}
+
+fn create_magics(
+    magic_numbers: &[u64; 64],
+    index_bits: &[u8; 64],
+    directions: &[(i8, i8); 4],
+    attacks: &mut Vec<u64>,
+) -> Vec<Magic> {
+    let mut magics = Vec::new();
+    for (position, magic_key) in magic_numbers.iter().enumerate() {
+        let relevant_fields = get_relevant_fields(position, directions);
+        let shift = 64 - index_bits[position];
+        let offset = attacks.len();
+        attacks.resize(offset + (1 << (64 - shift)), 0);
+
+        // every subset of the relevant fields (carry rippler)
+        let mut blockers: u64 = 0;
+        loop {
+            let index = (blockers.wrapping_mul(*magic_key) >> shift) as usize;
+            let moves = get_attacks(position, blockers, directions);
+            let entry = &mut attacks[offset + index];
+            assert!(*entry == 0 || *entry == moves, "magic of field {} has a collision", position);
+            *entry = moves;
+            blockers = blockers.wrapping_sub(relevant_fields) & relevant_fields;
+            if blockers == 0 {
+                break;
+            }
+        }
+        magics.push(Magic { relevant_fields, magic_key: *magic_key, shift, offset });
+    }
+    magics
+}
+
+fn get_relevant_fields(position: usize, directions: &[(i8, i8); 4]) -> u64 {
+    let mut fields = 0;
+    for &direction in directions {
+        let ray = get_ray(position, direction);
+        // the last field of a ray can not block anything
+        for &field in ray.iter().take(ray.len().saturating_sub(1)) {
+            fields |= 1 << field;
+        }
+    }
+    fields
+}
+
+fn get_attacks(position: usize, blockers: u64, directions: &[(i8, i8); 4]) -> u64 {
+    let mut attacks = 0;
+    for &direction in directions {
+        for field in get_ray(position, direction) {
+            attacks |= 1 << field;
+            if blockers & (1 << field) != 0 {
+                break;
+            }
+        }
+    }
+    attacks
+}
+
+fn get_ray(position: usize, (file_delta, rank_delta): (i8, i8)) -> Vec<usize> {
+    let mut ray = Vec::new();
+    let (mut file, mut rank) = ((position % 8) as i8, (position / 8) as i8);
+    loop {
+        file += file_delta;
+        rank += rank_delta;
+        if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+            return ray;
+        }
+        ray.push((rank * 8 + file) as usize);
+    }
+}
+
+fn write_magics(output: &mut String, name: &str, magics: &[Magic]) {
+    writeln!(output, "pub static {}: [MagicBitboard; 64] = [", name).unwrap();
+    for magic in magics {
+        writeln!(
+            output,
+            "    MagicBitboard {{ relevant_fields: Bitboard {{ board: {:#x} }}, magic_key: {:#x}, index: {}, offset: {} }},",
+            magic.relevant_fields, magic.magic_key, magic.shift, magic.offset
+        )
+        .unwrap();
+    }
+    writeln!(output, "];").unwrap();
+}
@@ -0,0 +1,154 @@
+// precalculated magics (see find.rs) - read by build.rs to generate the attack tables
+// index bits = size of the table of the field (1 << bits), printed by the "magics" command together with the numbers
+pub const ROOK_MAGIC_NUMBERS: [u64; 64] = [
+    4647719420192360576,
+    90073368279130112,
+    2377909743478964288,
+    9259425091857418240,
+    9295448322724602922,
+    2377906103292789288,
+    7061670750025449732,
+    72075324786999426,
+    4644372549247105,
+    2307039287796834305,
+    703824889122816,
+    11529355817924988929,
+    140754676615168,
+    562984607024640,
+    577586686637195792,
+    117234349274843264,
+    9167178198695936,
+    4508272587442368,
+    27024346811736192,
+    24912185377689600,
+    2311504394870327296,
+    2486269568833290376,
+    2882484081558438224,
+    434669931825774724,
+    2594425231233787008,
+    9223935132841345158,
+    9223935545155002436,
+    90089586889328640,
+    20266750226990080,
+    180216555012096128,
+    9372571383886250242,
+    9223513899626676356,
+    198160764098316419,
+    18031991773470720,
+    9225131392914960640,
+    4504150591668480,
+    146369223428408320,
+    140754676614656,
+    2410133850167478,
+    864695529756427649,
+    140876001083393,
+    162130858164117504,
+    13837873080204132384,
+    90353536245760040,
+    2891315358952620160,
+    7318557851516932,
+    423382978134050,
+    72343468145508353,
+    140781511778432,
+    576607270869148160,
+    2305914478006440192,
+    3459186932445610112,
+    1180506087221367296,
+    10736616970971514881,
+    648799898627760384,
+    4683679924736,
+    71468834619409,
+    9372009657998065669,
+    2364741691050590258,
+    1153203070315790361,
+    1689262464698370,
+    522980577076070410,
+    2486567540854621700,
+    9223408322953102758,
+];
+pub const BISHOP_MAGIC_NUMBERS: [u64; 64] = [
+    18581798595985920,
+    225182738905571460,
+    6757787516273664,
+    19144810413687869,
+    5634035288172288,
+    4972539206380290592,
+    4835185984818257984,
+    28430630773654530,
+    9818421395134612256,
+    9009776373563520,
+    2401335559456000,
+    2555282211766464,
+    1268879637090305,
+    2594920043729387520,
+    6917530201510380553,
+    1155475674489687108,
+    9227879210164486464,
+    1242157902694187540,
+    580964442129334528,
+    294985792793686018,
+    9233540346191872000,
+    4645591514875176,
+    18577902648035840,
+    290763681561051777,
+    1153635673113110528,
+    22535590627770624,
+    4611835587710706208,
+    18023332142252096,
+    9288743001276416,
+    565148981395976,
+    5630066537136452,
+    288514136067819776,
+    9223970196951142432,
+    10412465413526924289,
+    9223935691202267136,
+    85570594092351616,
+    1747396930599846144,
+    2315186695537428480,
+    73184594530403332,
+    9228037300125303298,
+    5371176992639041544,
+    81489239677749008,
+    324364866544480256,
+    1171012602776520322,
+    2323927916355780866,
+    577058903859201056,
+    1730516955124998404,
+    2252629023392257,
+    9547776398712375298,
+    871587866948538377,
+    2551315872874498,
+    36063983605907584,
+    17662684037184,
+    144264859230438794,
+    1130366712020996,
+    27171136886308864,
+    1408614787190784,
+    1127550317371395,
+    45064723301402626,
+    11605776399524300304,
+    2305843009282933249,
+    74315040728023168,
+    579141517412139520,
+    1206966108893020232,
+];
+pub const ROOK_INDEX_BITS: [u8; 64] = [
+    12, 11, 11, 11, 11, 11, 11, 12,
+    11, 10, 10, 10, 10, 10, 10, 11,
+    11, 10, 10, 10, 10, 10, 10, 11,
+    11, 10, 10, 10, 10, 10, 10, 11,
+    11, 10, 10, 10, 10, 10, 10, 11,
+    11, 10, 10, 10, 10, 10, 10, 11,
+    11, 10, 10, 10, 10, 10, 10, 11,
+    12, 11, 11, 11, 11, 11, 11, 12,
+];
+pub const BISHOP_INDEX_BITS: [u8; 64] = [
+    6, 5, 5, 5, 5, 5, 5, 6,
+    5, 5, 5, 5, 5, 5, 5, 5,
+    5, 5, 7, 7, 7, 7, 5, 5,
+    5, 5, 7, 9, 9, 7, 5, 5,
+    5, 5, 7, 9, 9, 7, 5, 5,
+    5, 5, 7, 7, 7, 7, 5, 5,
+    5, 5, 5, 5, 5, 5, 5, 5,
+    6, 5, 5, 5, 5, 5, 5, 6,
+];
@@ -1,6 +1,14 @@
-use std::usize;
+use std::{
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
+    usize,
+};
 
-#[derive(Clone, Debug, Copy)]
+use crate::{BETWEEN_FIELDS, LINE_FIELDS};
+
+const NOT_FILE_A: u64 = !0x0101_0101_0101_0101;
+const NOT_FILE_H: u64 = !0x8080_8080_8080_8080;
+
+#[derive(Clone, Debug, Copy, Default, PartialEq, Eq)]
 pub struct Bitboard{
     pub board: u64
 }
@@ -44,10 +52,157 @@ impl Bitboard{
             iterated_board &= iterated_board -1;
         }
     } 
+
+    pub fn is_empty(&self) -> bool {
+        self.board == 0
+    }
+
+    // flip along the horizontal axis - first rank <-> eighth rank
+    pub fn mirror(self) -> Bitboard {
+        Bitboard {
+            board: self.board.swap_bytes(),
+        }
+    }
+
+    // flip along the vertical axis - a file <-> h file
+    pub fn flip_horizontal(self) -> Bitboard {
+        Bitboard {
+            board: self.board.reverse_bits().swap_bytes(),
+        }
+    }
+
+    // shifts never wrap around the board - figures on the edge just drop out
+    pub fn north(self) -> Bitboard {
+        Bitboard { board: self.board << 8 }
+    }
+
+    pub fn south(self) -> Bitboard {
+        Bitboard { board: self.board >> 8 }
+    }
+
+    pub fn east(self) -> Bitboard {
+        Bitboard { board: (self.board & NOT_FILE_H) << 1 }
+    }
+
+    pub fn west(self) -> Bitboard {
+        Bitboard { board: (self.board & NOT_FILE_A) >> 1 }
+    }
+
+    pub fn north_east(self) -> Bitboard {
+        Bitboard { board: (self.board & NOT_FILE_H) << 9 }
+    }
+
+    pub fn north_west(self) -> Bitboard {
+        Bitboard { board: (self.board & NOT_FILE_A) << 7 }
+    }
+
+    pub fn south_east(self) -> Bitboard {
+        Bitboard { board: (self.board & NOT_FILE_H) >> 7 }
+    }
+
+    pub fn south_west(self) -> Bitboard {
+        Bitboard { board: (self.board & NOT_FILE_A) >> 9 }
+    }
 }
 
-#[cfg(test)]
+// fields strictly between both fields if they share a line or diagonal - empty otherwise
+pub fn between(from: usize, to: usize) -> Bitboard {
+    BETWEEN_FIELDS[from][to]
+}
+
+// whole line or diagonal through both fields (edge to edge) - empty if they are not aligned
+pub fn line(from: usize, to: usize) -> Bitboard {
+    LINE_FIELDS[from][to]
+}
+
+// iterates over all used fields starting at a1
+impl Iterator for Bitboard {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.board == 0 {
+            return None;
+        }
+        let field = self.board.trailing_zeros() as usize;
+        self.board &= self.board - 1;
+        Some(field)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.board.count_ones() as usize;
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for Bitboard {}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Bitboard) -> Bitboard {
+        Bitboard { board: self.board & rhs.board }
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
 
+    fn bitor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard { board: self.board | rhs.board }
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+
+    fn bitxor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard { board: self.board ^ rhs.board }
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard { board: !self.board }
+    }
+}
+
+impl Shl<usize> for Bitboard {
+    type Output = Bitboard;
+
+    fn shl(self, rhs: usize) -> Bitboard {
+        Bitboard { board: self.board << rhs }
+    }
+}
+
+impl Shr<usize> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, rhs: usize) -> Bitboard {
+        Bitboard { board: self.board >> rhs }
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Bitboard) {
+        self.board &= rhs.board;
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Bitboard) {
+        self.board |= rhs.board;
+    }
+}
+
+impl BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, rhs: Bitboard) {
+        self.board ^= rhs.board;
+    }
+}
+
+#[cfg(test)]
 mod tests{
 
     use super::*;
@@ -75,4 +230,67 @@ mod tests{
         let used = board.get_used_fields();
         assert_eq!(3, used.len());
     }
+
+    #[test]
+    fn test_operators_and_iterator(){
+        let a = Bitboard { board: 0b1100 };
+        let b = Bitboard { board: 0b1010 };
+        assert_eq!(0b1000, (a & b).board);
+        assert_eq!(0b1110, (a | b).board);
+        assert_eq!(0b0110, (a ^ b).board);
+        assert_eq!(!0b1100, (!a).board);
+        assert_eq!(0b11000, (a << 1).board);
+        assert_eq!(0b110, (a >> 1).board);
+
+        let mut c = a;
+        c |= b;
+        c &= !Bitboard { board: 0b10 };
+        assert_eq!(0b1100, c.board);
+
+        assert_eq!(vec![2, 3], a.collect::<Vec<usize>>());
+        assert_eq!(2, a.len());
+        assert!(Bitboard::new().is_empty());
+    }
+
+    #[test]
+    fn test_shifts_do_not_wrap(){
+        // a4 and h4
+        let edges = Bitboard { board: 1 << 24 | 1 << 31 };
+        assert_eq!(vec![25], edges.east().collect::<Vec<usize>>());
+        assert_eq!(vec![30], edges.west().collect::<Vec<usize>>());
+        assert_eq!(vec![32, 39], edges.north().collect::<Vec<usize>>());
+        assert_eq!(vec![16, 23], edges.south().collect::<Vec<usize>>());
+        assert_eq!(vec![33], edges.north_east().collect::<Vec<usize>>());
+        assert_eq!(vec![38], edges.north_west().collect::<Vec<usize>>());
+        assert_eq!(vec![17], edges.south_east().collect::<Vec<usize>>());
+        assert_eq!(vec![22], edges.south_west().collect::<Vec<usize>>());
+        // leaving the board at the top
+        assert!(Bitboard { board: 1 << 60 }.north().is_empty());
+    }
+
+    #[test]
+    fn test_mirror_and_flip(){
+        // b2 and h4
+        let board = Bitboard { board: 1 << 9 | 1 << 31 };
+        assert_eq!(vec![39, 49], board.mirror().collect::<Vec<usize>>());
+        assert_eq!(vec![14, 24], board.flip_horizontal().collect::<Vec<usize>>());
+        assert_eq!(board, board.mirror().mirror());
+        assert_eq!(board, board.flip_horizontal().flip_horizontal());
+    }
+
+    #[test]
+    fn test_between_and_line(){
+        // a1 - h8
+        assert_eq!(vec![9, 18, 27, 36, 45, 54], between(0, 63).collect::<Vec<usize>>());
+        assert_eq!(between(0, 63), between(63, 0));
+        assert_eq!(8, line(9, 18).len());
+        // e1 - e4
+        assert_eq!(vec![12, 20], between(4, 28).collect::<Vec<usize>>());
+        assert_eq!(0x1010_1010_1010_1010, line(4, 28).board);
+        // neighbours and not aligned fields
+        assert!(between(0, 1).is_empty());
+        assert!(between(0, 17).is_empty());
+        assert!(line(0, 17).is_empty());
+        assert!(line(5, 5).is_empty());
+    }
 }
\ No newline at end of file
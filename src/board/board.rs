@@ -1,21 +1,20 @@
 use crate::{
     board::fen::FEN,
     figures::{color::Color, piece::Piece},
-    helper::{
-        movement::{figure_can_move_left, figure_can_move_right},
-        position_to_usize::{
-            get_position_id, get_validated_position_from_input,
-        },
-    },
-    ZOBRIST_CASTLE_NUMBERS, ZOBRIST_CURRENT_MOVE, ZOBRIST_EN_PASSANT, ZOBRIST_FIGURE_NUMBERS,
-    ZOBRIST_SEED,
+    helper::position_to_usize::get_validated_position_from_input,
+    engine::transposition::zobrist::get_en_passant_key,
+    ZOBRIST_CASTLE_NUMBERS, ZOBRIST_CURRENT_MOVE, ZOBRIST_FIGURE_NUMBERS,
 };
 use std::usize;
+#[cfg(test)]
+use crate::figures::piece::ALL_PIECES;
 
 use super::{
     bitboard::Bitboard,
     castle::Castle,
+    chess_move::{Move, MoveType},
     promotion::{convert_promotion_to_figure, Promotion},
+    square::{File, Rank, Square},
 };
 
 #[derive(Clone, Copy)]
@@ -58,7 +57,7 @@ impl Default for Chessboard {
             castle: Castle {
                 ..Default::default()
             },
-            zobrist_key: *ZOBRIST_SEED,
+            zobrist_key: *ZOBRIST_CURRENT_MOVE,
         };
         board.set_to_default();
         board
@@ -73,7 +72,9 @@ impl Chessboard {
             ..Default::default()
         };
         board.set_empty();
-        board.current_move = color;
+        if color == Color::Black {
+            board.set_current_move();
+        }
         board
     }
 
@@ -103,7 +104,8 @@ impl Chessboard {
         self.castle = Castle {
             ..Default::default()
         };
-        self.zobrist_key = *ZOBRIST_SEED
+        // white to move
+        self.zobrist_key = *ZOBRIST_CURRENT_MOVE
     }
     fn set_current_move(&mut self) {
         self.zobrist_key ^= *ZOBRIST_CURRENT_MOVE;
@@ -131,7 +133,7 @@ impl Chessboard {
         self.zobrist_key ^= ZOBRIST_FIGURE_NUMBERS[color as usize][piece as usize][position];
     }
 
-    fn get_type_of_figure(&self, color: Color, position: usize) -> Option<Piece> {
+    pub fn get_type_of_figure(&self, color: Color, position: usize) -> Option<Piece> {
         // not beautiful, but faster than array of pieces
         if self.get_pieces(color, Piece::Pawn).field_is_used(position) {
             return Some(Piece::Pawn);
@@ -160,17 +162,6 @@ impl Chessboard {
         None
     }
 
-    pub fn is_queen_or_rook(&self, color: Color, position: usize) -> bool {
-        self.get_pieces(color, Piece::Rook).field_is_used(position)
-            || self.get_pieces(color, Piece::Queen).field_is_used(position)
-    }
-
-    pub fn is_queen_or_bishop(&self, color: Color, position: usize) -> bool {
-        self.get_pieces(color, Piece::Bishop)
-            .field_is_used(position)
-            || self.get_pieces(color, Piece::Queen).field_is_used(position)
-    }
-
     pub fn get_opponents(&self) -> &Bitboard {
         match self.current_move {
             Color::White => &self.used_positions[Color::Black as usize],
@@ -185,27 +176,55 @@ impl Chessboard {
         }
     }
 
-    pub fn get_opponent_piece(&self, piece: Piece) -> &Bitboard {
-        match self.current_move {
-            Color::White => &self.figures[Color::Black as usize][piece as usize],
-            Color::Black => &self.figures[Color::White as usize][piece as usize],
-        }
-    }
-
     pub fn get_positions_by_current_player(&self) -> &Bitboard {
         &self.used_positions[self.current_move as usize]
     }
 
     pub fn update_position_from_uci_input(&mut self, mov: &str) {
-        if let Some(possible_move) = get_validated_position_from_input(mov) {
-            self.move_figure(
-                possible_move.from,
-                possible_move.to,
-                possible_move.promoted_to,
-            );
+        if let Some(possible_move) = self.get_move_from_uci_input(mov) {
+            self.move_figure(possible_move);
         }
     }
 
+    pub fn get_move_from_uci_input(&self, mov: &str) -> Option<Move> {
+        let (from, to, promoted_to) = get_validated_position_from_input(mov)?;
+        Some(self.create_move(from, to, promoted_to))
+    }
+
+    // get type of move for moves we did not generate ourself (UCI input, opening book)
+    pub fn create_move(&self, from: usize, to: usize, promoted_to: Option<Promotion>) -> Move {
+        let is_capture = self.get_opponents().field_is_used(to);
+        if let Some(promotion) = promoted_to {
+            return Move::promotion(from, to, promotion, is_capture);
+        }
+        if self.get_pieces(self.current_move, Piece::King).field_is_used(from) {
+            // white castle 4 -> 2/6 || black castle 60 -> 58/62
+            let king_start_field = match self.current_move {
+                Color::White => 4,
+                Color::Black => 60,
+            };
+            if from == king_start_field && to == from + 2 {
+                return Move::new(from, to, MoveType::KingCastle);
+            }
+            if from == king_start_field && to + 2 == from {
+                return Move::new(from, to, MoveType::QueenCastle);
+            }
+        }
+        if self.get_pieces(self.current_move, Piece::Pawn).field_is_used(from) {
+            if from.abs_diff(to) == 16 {
+                return Move::new(from, to, MoveType::DoublePawnPush);
+            }
+            // pawn moving to other column without a figure to capture
+            if Square::from_index(from).file() != Square::from_index(to).file() && !is_capture {
+                return Move::new(from, to, MoveType::EnPassant);
+            }
+        }
+        if is_capture {
+            return Move::new(from, to, MoveType::Capture);
+        }
+        Move::new(from, to, MoveType::Quiet)
+    }
+
     fn update_figure_to_promoted_one(
         &mut self,
         old_field: usize,
@@ -221,160 +240,61 @@ impl Chessboard {
         self.add_piece(self.current_move, new_piece, new_field);
     }
 
-    fn possible_future_en_passant(&mut self, old_field: usize, new_field: usize) {
+    // field of the pawn that is captured en passant
+    fn get_en_passanted_field(&self, new_field: usize) -> usize {
         match self.current_move {
-            Color::White => self.possible_white_en_passant(old_field, new_field),
-            Color::Black => self.possible_black_en_passant(old_field, new_field),
-        }
-    }
-
-    fn possible_white_en_passant(&mut self, old_field: usize, new_field: usize) {
-        if new_field == old_field + 16 {
-            let white_pawns = self.get_pieces(Color::White, Piece::Pawn);
-            if white_pawns.field_is_used(old_field) {
-                self.en_passant = Some(new_field);
-                self.zobrist_key ^= ZOBRIST_EN_PASSANT[new_field];
-                return;
-            }
+            Color::White => new_field - 8,
+            Color::Black => new_field + 8,
         }
-        self.en_passant = None;
-    }
-
-    fn possible_black_en_passant(&mut self, old_field: usize, new_field: usize) {
-        if old_field == new_field + 16 {
-            let black_pawns = self.get_pieces(Color::Black, Piece::Pawn);
-            if black_pawns.field_is_used(old_field) {
-                self.en_passant = Some(new_field);
-                self.zobrist_key ^= ZOBRIST_EN_PASSANT[new_field];
-                return;
-            }
-        }
-        self.en_passant = None;
     }
 
-    fn check_and_execute_en_passant(&mut self, old_field: usize, new_field: usize) {
-        // no possible en passant no need to check any longer
-        if self.en_passant.is_none() {
-            return;
-        }
-        // we have none checked en_passant at this point
-        let en_passanted_figure = self.en_passant.unwrap();
-        // remove possible prev. en passant from zobrist
-        self.zobrist_key ^= ZOBRIST_EN_PASSANT[en_passanted_figure];
-        let pawns = self.get_pieces(self.current_move, Piece::Pawn);
-
-        // check if figure moving is actually a pawn
-        if !pawns.field_is_used(old_field) {
-            return;
-        }
-
-        let is_en_passant = match self.current_move {
-            Color::White => self.is_en_passant_white(old_field, new_field),
-            Color::Black => self.is_en_passant_black(old_field, new_field),
+    fn castle(&mut self, move_type: MoveType) {
+        let (rook_from, rook_to) = match (self.current_move, move_type) {
+            (Color::White, MoveType::KingCastle) => (7, 5),
+            (Color::White, _) => (0, 3),
+            // lange rutsche
+            (Color::Black, MoveType::QueenCastle) => (56, 59),
+            (Color::Black, _) => (63, 61),
         };
-        if !is_en_passant {
-            return;
-        }
-        self.remove_piece(self.get_opponent_color(), Piece::Pawn, en_passanted_figure);
+        self.move_own_figure(rook_from, rook_to);
     }
 
-    fn is_en_passant_black(&mut self, old_field: usize, new_field: usize) -> bool {
-        if let Some(possible_en_passant_field) = self.en_passant {
-            // post en passant would be one column less than possible en passant"ed" pawn
-            if possible_en_passant_field - 8 != new_field {
-                return false;
-            }
-            if figure_can_move_left(&old_field) && possible_en_passant_field + 1 == old_field {
-                return true;
-            }
-            if figure_can_move_right(&old_field) && possible_en_passant_field - 1 == old_field {
-                return true;
-            }
+    pub fn move_figure(&mut self, mov: Move) {
+        let from = mov.from();
+        let to = mov.to();
+        // remove possible prev. en passant - it is only possible directly after the move
+        if let Some(en_passant) = self.en_passant.take() {
+            self.zobrist_key ^= get_en_passant_key(self, en_passant, self.current_move);
         }
-        false
-    }
-
-    fn is_en_passant_white(&mut self, old_field: usize, new_field: usize) -> bool {
-        if let Some(possible_en_passant_field) = self.en_passant {
-            // post en passant would be one column more than possible en passant"ed" pawn
-            if possible_en_passant_field + 8 != new_field {
-                return false;
-            }
-            if figure_can_move_left(&old_field) && possible_en_passant_field + 1 == old_field {
-                return true;
+        match mov.move_type() {
+            // if move is caste move rook prior to moving king
+            MoveType::KingCastle | MoveType::QueenCastle => self.castle(mov.move_type()),
+            // remove opponent (from field we did not move to!)
+            MoveType::EnPassant => {
+                let en_passanted_figure = self.get_en_passanted_field(to);
+                self.remove_piece(self.get_opponent_color(), Piece::Pawn, en_passanted_figure);
             }
-            if figure_can_move_right(&old_field) && possible_en_passant_field - 1 == old_field {
-                return true;
+            MoveType::DoublePawnPush => {
+                self.en_passant = Some(to);
+                self.zobrist_key ^= get_en_passant_key(self, to, self.get_opponent_color());
             }
+            _ => {}
         }
-        false
-    }
-
-    fn castle(&mut self, old_field: usize, new_field: usize) {
-        // white castle 4 -> 2/6 || black castle 60 -> 58/62
-        if !((old_field == 4 && (new_field == 2 || new_field == 6))
-            || ((old_field == 60) && (new_field == 58 || new_field == 62)))
-            // side is not able to castle
-            || !self.castle.can_castle(self.current_move)
-            // figure moving is not the king
-            || !self.get_pieces(self.current_move, Piece::King).field_is_used(old_field)
-        {
-            return;
-        }
-
-        if old_field == 4 && self.current_move.eq(&Color::White) {
-            self.white_castle(new_field);
-        } else if old_field == 60 && self.current_move.eq(&Color::Black) {
-            self.black_castle(new_field);
-        }
-    }
-
-    fn black_castle(&mut self, new_field: usize) {
-        self.castle.set_has_castled(self.current_move);
-        self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[2];
-        self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[3];
 
-        // lange rutsche
-        if new_field == 58 {
-            self.move_black_figure(56, 59);
-        }
-        if new_field == 62 {
-            self.move_black_figure(63, 61);
+        if let Some(promoted_figure) = mov.promoted_to() {
+            self.update_figure_to_promoted_one(from, to, promoted_figure);
+        } else {
+            self.move_own_figure(from, to);
         }
+        self.update_castle_rights(from);
+        self.update_castle_rights(to);
+        self.set_current_move();
     }
 
-    fn white_castle(&mut self, new_field: usize) {
-        self.castle.set_has_castled(self.current_move);
-        self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[0];
-        self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[1];
-
-        if new_field == 2 {
-            self.move_white_figure(0, 3);
-        }
-        if new_field == 6 {
-            self.move_white_figure(7, 5);
-        }
-    }
-
-    pub fn move_figure(&mut self, from: usize, to: usize, promoted_to: Option<Promotion>) {
-        if let Some(promoted_figure) = promoted_to {
-            self.update_figure_to_promoted_one(from, to, promoted_figure);
-
-            if let Some(en_passant) = self.en_passant {
-                self.zobrist_key ^= ZOBRIST_EN_PASSANT[en_passant];
-                self.en_passant = None;
-            }
-        } else {
-            // if move is caste move rook prior to moving king
-            self.castle(from, to);
-            // if move is en passant remove opponent (from field we did not move to!)
-            self.check_and_execute_en_passant(from, to);
-            // check for possible future en_passant options
-            self.possible_future_en_passant(from, to);
-            match self.current_move {
-                Color::White => self.move_white_figure(from, to),
-                Color::Black => self.move_black_figure(from, to),
-            }
+    // pass the turn (null move pruning) - en passant is only possible directly after the double push
+    pub fn make_null_move(&mut self) {
+        if let Some(en_passant) = self.en_passant.take() {
+            self.zobrist_key ^= get_en_passant_key(self, en_passant, self.current_move);
         }
         self.set_current_move();
     }
@@ -389,65 +309,40 @@ impl Chessboard {
         }
     }
 
-    fn move_black_figure(&mut self, from: usize, to: usize) {
-        self.remove_opponent_piece_from_field(to, Color::White);
-        if let Some(black_piece) = self.get_type_of_figure(Color::Black, from) {
-            self.remove_piece(Color::Black, black_piece, from);
-            self.add_piece(Color::Black, black_piece, to);
+    fn move_own_figure(&mut self, from: usize, to: usize) {
+        self.remove_opponent_piece_from_field(to, self.get_opponent_color());
+        if let Some(piece) = self.get_type_of_figure(self.current_move, from) {
+            self.remove_piece(self.current_move, piece, from);
+            self.add_piece(self.current_move, piece, to);
         }
-        if (from == 56 || from == 60 || from == 63) && self.castle.black_can_castle() {
-            if self.castle.black_castle_short && from == 63 {
-                self.castle.black_castle_short = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[2];
-            } else if self.castle.black_castle_long && from == 56 {
-                self.castle.black_castle_long = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[3];
-            } else if self.castle.black_can_castle() && from == 60 {
-                self.castle.black_castle_long = false;
-                self.castle.black_castle_short = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[2];
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[3];
+    }
+
+    // moving from or to king and rook starting fields removes castle rights (moved or captured)
+    fn update_castle_rights(&mut self, field: usize) {
+        match field {
+            4 => {
+                self.remove_castle_right(7);
+                self.remove_castle_right(0);
             }
-        }
-        if (to == 0 || to == 7) && self.castle.white_can_castle() {
-            if self.castle.white_castle_short && to == 7 {
-                self.castle.white_castle_short = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[0];
-            } else if self.castle.white_castle_long && to == 0 {
-                self.castle.white_castle_long = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[1];
+            60 => {
+                self.remove_castle_right(63);
+                self.remove_castle_right(56);
             }
+            _ => self.remove_castle_right(field),
         }
     }
 
-    fn move_white_figure(&mut self, from: usize, to: usize) {
-        self.remove_opponent_piece_from_field(to, Color::Black);
-        if let Some(white_piece) = self.get_type_of_figure(Color::White, from) {
-            self.remove_piece(Color::White, white_piece, from);
-            self.add_piece(Color::White, white_piece, to);
-        }
-        if (from == 0 || from == 4 || from == 7) && self.castle.white_can_castle() {
-            if self.castle.white_castle_short && from == 7 {
-                self.castle.white_castle_short = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[0];
-            } else if self.castle.white_castle_long && from == 0 {
-                self.castle.white_castle_long = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[1];
-            } else if self.castle.white_can_castle() && from == 4 {
-                self.castle.white_castle_long = false;
-                self.castle.white_castle_short = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[0];
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[1];
-            }
-        }
-        if (to == 56 || to == 63) && self.castle.black_can_castle() {
-            if self.castle.black_castle_short && to == 63 {
-                self.castle.black_castle_short = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[2];
-            } else if self.castle.black_castle_long && to == 56 {
-                self.castle.black_castle_long = false;
-                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[3];
-            }
+    fn remove_castle_right(&mut self, rook_field: usize) {
+        let (castle_right, zobrist_index) = match rook_field {
+            7 => (&mut self.castle.white_castle_short, 0),
+            0 => (&mut self.castle.white_castle_long, 1),
+            63 => (&mut self.castle.black_castle_short, 2),
+            56 => (&mut self.castle.black_castle_long, 3),
+            _ => return,
+        };
+        if *castle_right {
+            *castle_right = false;
+            self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[zobrist_index];
         }
     }
 
@@ -462,7 +357,7 @@ impl Chessboard {
         self.set_empty();
         let mut current_position: usize = 56;
         let mut current_state = FEN::FIGURES;
-        let mut en_passant_row = ' ';
+        let mut en_passant_file = None;
         for c in position.chars() {
             if c == ' ' {
                 current_state = current_state.update_to_next_state();
@@ -510,13 +405,13 @@ impl Chessboard {
                     self.current_move = Color::Black;
                 }
             } else if current_state == FEN::ENPASSANT {
-                if c.is_alphabetic() {
-                    en_passant_row = c;
-                } else if c != '-' {
-                    let possible_en_passanted =
-                        get_position_id(&en_passant_row.to_string(), c.to_digit(10).unwrap() as u8);
-                    self.zobrist_key ^= ZOBRIST_EN_PASSANT[possible_en_passanted];
-                    self.en_passant = Some(possible_en_passanted);
+                if let Some(file) = File::from_char(c) {
+                    en_passant_file = Some(file);
+                } else if let (Some(file), Some(rank)) = (en_passant_file, Rank::from_char(c)) {
+                    let possible_en_passanted = get_en_passanted_square(Square::new(file, rank));
+                    self.en_passant = Some(possible_en_passanted.index());
+                    self.zobrist_key ^=
+                        get_en_passant_key(self, possible_en_passanted.index(), self.current_move);
                 }
             }
         }
@@ -536,11 +431,97 @@ impl Chessboard {
     }
 }
 
+// boards for the tests
+#[cfg(test)]
+impl Chessboard {
+    pub fn from_fen(fen: &str) -> Chessboard {
+        let mut board = Chessboard {
+            ..Default::default()
+        };
+        board.create_position_from_input_string(String::from(fen));
+        board
+    }
+
+    // same position with swapped colors - flipped along the horizontal axis (a1 <-> a8)
+    // the evaluation of the mirrored board has to be the negated evaluation of this board
+    pub fn mirrored(&self) -> Chessboard {
+        let mut board = Chessboard::empty(self.get_opponent_color());
+        for color in [Color::White, Color::Black] {
+            let mirrored_color = match color {
+                Color::White => Color::Black,
+                Color::Black => Color::White,
+            };
+            for piece in ALL_PIECES {
+                for position in *self.get_pieces(color, piece) {
+                    board.add_piece(mirrored_color, piece, Square::from_index(position).mirror().index());
+                }
+            }
+        }
+        board.set_castle_rights(Castle {
+            white_castle_long: self.castle.black_castle_long,
+            white_castle_short: self.castle.black_castle_short,
+            black_castle_long: self.castle.white_castle_long,
+            black_castle_short: self.castle.white_castle_short,
+        });
+        if let Some(en_passant) = self.en_passant {
+            board.set_en_passant(Square::from_index(en_passant).mirror().index());
+        }
+        board
+    }
+
+    // flipped along the vertical axis (a1 <-> h1) - castle rights are lost as king and rooks change their sides
+    pub fn flipped_horizontal(&self) -> Chessboard {
+        let mut board = Chessboard::empty(self.current_move);
+        for color in [Color::White, Color::Black] {
+            for piece in ALL_PIECES {
+                for position in *self.get_pieces(color, piece) {
+                    board.add_piece(color, piece, Square::from_index(position).flip_horizontal().index());
+                }
+            }
+        }
+        if let Some(en_passant) = self.en_passant {
+            board.set_en_passant(Square::from_index(en_passant).flip_horizontal().index());
+        }
+        board
+    }
+
+    fn set_castle_rights(&mut self, castle: Castle) {
+        let castle_rights = [
+            castle.white_castle_short,
+            castle.white_castle_long,
+            castle.black_castle_short,
+            castle.black_castle_long,
+        ];
+        for (index, castle_right) in castle_rights.iter().enumerate() {
+            if *castle_right {
+                self.zobrist_key ^= ZOBRIST_CASTLE_NUMBERS[index];
+            }
+        }
+        self.castle = castle;
+    }
+
+    // only used for a new board - key of the previous en passant is not removed
+    fn set_en_passant(&mut self, en_passant: usize) {
+        self.en_passant = Some(en_passant);
+        self.zobrist_key ^= get_en_passant_key(self, en_passant, self.current_move);
+    }
+}
+
+// FEN stores the field behind the pawn (e3), we store the pawn which can be taken (e4)
+// the opening book uses the field of the pawn - keep both working
+fn get_en_passanted_square(square: Square) -> Square {
+    match square.rank() {
+        Rank::THIRD => Square::new(square.file(), Rank::FOURTH),
+        Rank::SIXTH => Square::new(square.file(), Rank::FIFTH),
+        _ => square,
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::{engine::count::count_moves, make_move};
+    use crate::{engine::{engine::SearchOptions, perft::perft}, make_move};
 
     #[test]
     fn short_castle_white() {
@@ -654,7 +635,7 @@ mod tests {
 
         board.figures[Color::Black as usize][Piece::Pawn as usize].set_field(52);
         board.positions.set_field(52);
-        board.move_figure(52, 36, None);
+        board.move_figure(Move::new(52, 36, MoveType::DoublePawnPush));
         assert_eq!(board.en_passant, Some(36));
     }
 
@@ -664,7 +645,7 @@ mod tests {
 
         board.figures[Color::Black as usize][Piece::Pawn as usize].set_field(52);
         board.positions.set_field(52);
-        board.move_figure(52, 44, None);
+        board.move_figure(Move::new(52, 44, MoveType::Quiet));
 
         assert_eq!(board.en_passant, None);
     }
@@ -676,7 +657,7 @@ mod tests {
         board.figures[Color::White as usize][Piece::Pawn as usize].set_field(26);
         board.figures[Color::Black as usize][Piece::Pawn as usize].set_field(25);
 
-        board.move_figure(25, 18, None);
+        board.move_figure(Move::new(25, 18, MoveType::EnPassant));
 
         assert_eq!(
             0,
@@ -694,7 +675,7 @@ mod tests {
         board.en_passant = Some(36);
         board.figures[Color::White as usize][Piece::Pawn as usize].set_field(35);
         board.figures[Color::Black as usize][Piece::Pawn as usize].set_field(36);
-        board.move_figure(35, 44, None);
+        board.move_figure(Move::new(35, 44, MoveType::EnPassant));
 
         assert_eq!(
             0,
@@ -711,38 +692,26 @@ mod tests {
         let mut board = Chessboard::empty(Color::White);
         board.castle = Castle{white_castle_long: true, white_castle_short: true, black_castle_long: true, black_castle_short: true};
         board.add_piece(Color::White, Piece::Queen, 4);
-
-        board.zobrist_key = 1234; // kinda stupid but using the zobrist to check if first if breaks function
-        board.castle(4, 2);
-        assert_eq!(board.zobrist_key, 1234);
+        assert_eq!(MoveType::Quiet, board.create_move(4, 2, None).move_type());
 
         let mut board = Chessboard::empty(Color::White);
         board.castle = Castle{white_castle_long: true, white_castle_short: true, black_castle_long: true, black_castle_short: true};
         board.add_piece(Color::White, Piece::Rook, 4);
-
-        board.zobrist_key = 1234;
-        board.castle(4, 6);
-        assert_eq!(board.zobrist_key, 1234);
+        assert_eq!(MoveType::Quiet, board.create_move(4, 6, None).move_type());
 
         // checky not even possible move (black king from starting position of white king two moves to the left) 
         // -> Should still be no castle
         let mut board = Chessboard::empty(Color::Black);
         board.castle = Castle{white_castle_long: true, white_castle_short: true, black_castle_long: true, black_castle_short: true};
         board.add_piece(Color::Black, Piece::King, 4);
-
-        board.zobrist_key = 1234;
-        board.castle(4, 2);
-        assert_eq!(board.zobrist_key, 1234);
-
+        assert_eq!(MoveType::Quiet, board.create_move(4, 2, None).move_type());
 
         // valid castle
         let mut board = Chessboard::empty(Color::White);
         board.castle = Castle{white_castle_long: true, white_castle_short: true, black_castle_long: true, black_castle_short: true};
         board.add_piece(Color::White, Piece::King, 4);
-
-        board.zobrist_key = 1234;
-        board.castle(4, 2);
-        assert_ne!(board.zobrist_key, 1234);
+        assert_eq!(MoveType::QueenCastle, board.create_move(4, 2, None).move_type());
+        assert_eq!(MoveType::KingCastle, board.create_move(4, 6, None).move_type());
     }
 
     #[test]
@@ -750,27 +719,46 @@ mod tests {
         let mut board = Chessboard::empty(Color::Black);
         board.castle = Castle{white_castle_long: true, white_castle_short: true, black_castle_long: true, black_castle_short: true};
         board.add_piece(Color::Black, Piece::Queen, 60);
-
-        board.zobrist_key = 1234; 
-        board.castle(60, 62);
-        assert_eq!(board.zobrist_key, 1234);
+        assert_eq!(MoveType::Quiet, board.create_move(60, 62, None).move_type());
 
         let mut board = Chessboard::empty(Color::Black);
         board.castle = Castle{white_castle_long: true, white_castle_short: true, black_castle_long: true, black_castle_short: true};
         board.add_piece(Color::Black, Piece::Rook, 60);
+        assert_eq!(MoveType::Quiet, board.create_move(60, 58, None).move_type());
+    }
+
+    #[test]
+    fn test_castle_rights_only_change_zobrist_once() {
+        // only short castle left - moving the king should not change the long castle key
+        let mut board = Chessboard::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        board.update_position_from_uci_input("e1d1");
+        board.update_position_from_uci_input("e8d8");
+
+        let expected = Chessboard::from_fen("3k4/8/8/8/8/8/8/3K3R w - - 0 1");
+        assert_eq!(expected.zobrist_key, board.zobrist_key);
+    }
 
-        board.zobrist_key = 1234; 
-        board.castle(60, 58);
-        assert_eq!(board.zobrist_key, 1234);
+    #[test]
+    fn test_null_move() {
+        let mut board = Chessboard::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        board.make_null_move();
+
+        let expected = Chessboard::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3");
+        assert_eq!(None, board.en_passant);
+        assert_eq!(Color::Black, board.current_move);
+        assert_eq!(expected.zobrist_key, board.zobrist_key);
+    }
+
+    #[test]
+    fn test_capturing_rook_with_promotion_removes_castle_right() {
+        let mut board = Chessboard::from_fen("r3k3/1P6/8/8/8/8/8/4K3 w q - 0 1");
+        board.update_position_from_uci_input("b7a8q");
+        assert!(!board.castle.black_castle_long);
     }
 
     #[test]
     fn test_position_creation() {
-        let position = String::from("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8");
-        let mut board = Chessboard {
-            ..Default::default()
-        };
-        board.create_position_from_input_string(position);
+        let board = Chessboard::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8");
 
         assert_eq!(
             true,
@@ -804,106 +792,62 @@ mod tests {
         let board = Chessboard {
             ..Default::default()
         };
-        let count = count_moves(&board, 4);
+        let count = perft(&board, 4, None);
         assert_eq!(197281, count);
     }
 
     #[test]
     fn test_black_en_passant_on_a_file() {
-        let mut board = Chessboard {
-            ..Default::default()
-        };
-        board.create_position_from_input_string(String::from("8/8/8/8/p6k/8/1P5K/8 w - - 0 1"));
-        board.move_figure(9, 25, None);
+        let mut board = Chessboard::from_fen("8/8/8/8/p6k/8/1P5K/8 w - - 0 1");
+        board.update_position_from_uci_input("b2b4");
 
-        let is_black_en_passant = board.is_en_passant_black(24, 17);
-        assert_eq!(true, is_black_en_passant)
+        let black_move = board.create_move(24, 17, None);
+        assert_eq!(MoveType::EnPassant, black_move.move_type())
     }
 
     #[test]
     fn test_black_en_passant_on_h_file() {
-        let mut board = Chessboard {
-            ..Default::default()
-        };
-        board.create_position_from_input_string(String::from("8/k2K4/8/8/7p/8/6P1/8 w - - 0 1"));
-        board.move_figure(14, 30, None);
-
-        let is_black_en_passant = board.is_en_passant_black(31, 22);
-        assert_eq!(true, is_black_en_passant)
-    }
-
-    #[test]
-    #[ignore]
-    fn test_position_2() {
-        let mut board = Chessboard::empty(Color::White);
-
-        let position_2 =
-            String::from("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -");
-        board.create_position_from_input_string(position_2);
-        let count = count_moves(&board, 4);
-        assert_eq!(4085603, count);
-    }
-
-    #[test]
-    #[ignore]
-    fn test_position_3() {
-        let mut board = Chessboard::empty(Color::White);
-        let position_3 = String::from("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1 ");
-        board.create_position_from_input_string(position_3);
-        let count = count_moves(&board, 5);
-        assert_eq!(674624, count);
-    }
-
-    #[test]
-    #[ignore]
-    fn test_position_4() {
-        let mut board = Chessboard::empty(Color::White);
+        let mut board = Chessboard::from_fen("8/k2K4/8/8/7p/8/6P1/8 w - - 0 1");
+        board.update_position_from_uci_input("g2g4");
 
-        let position_4 =
-            String::from("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
-        board.create_position_from_input_string(position_4);
-        let count = count_moves(&board, 4);
-        assert_eq!(422333, count);
+        let black_move = board.create_move(31, 22, None);
+        assert_eq!(MoveType::EnPassant, black_move.move_type())
     }
 
     #[test]
-    #[ignore]
-    fn test_position_5() {
-        // this test fails if it is run after the other tests for some reason I dont want to debug :D
-        let mut board = Chessboard::empty(Color::White);
+    fn test_en_passant_target_square_from_fen() {
+        let board = Chessboard::from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3");
+        let mut book_board = board;
+        book_board.create_position_from_input_string(String::from(
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d5 0 3",
+        ));
 
-        let position_5 =
-            String::from("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8  ");
-        board.create_position_from_input_string(position_5);
-        let count = count_moves(&board, 4);
-        assert_eq!(2103487, count);
+        assert_eq!(Some(35), board.en_passant);
+        assert_eq!(board.zobrist_key, book_board.zobrist_key);
+        assert_eq!(MoveType::EnPassant, board.create_move(36, 43, None).move_type());
     }
 
     #[test]
-    #[ignore]
-    fn test_position_6() {
-        // this test fails if it is run after the other tests for some reason I dont want to debug :D
-        let mut board = Chessboard::empty(Color::White);
-
-        let position_6 = String::from(
-            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10 ",
-        );
-        board.create_position_from_input_string(position_6);
-
-        let count = count_moves(&board, 4);
-        assert_eq!(3894594, count);
-    }
-
-    #[test]
-    #[ignore]
-    fn test_position_double_checked() {
-        let mut board = Chessboard::empty(Color::White);
-
-        let position = String::from("2Q3n1/R7/k7/8/8/8/P1r3P1/3K4 b - - 0 18");
-        board.create_position_from_input_string(position);
-
-        let count = count_moves(&board, 4);
-        assert_eq!(36899, count);
+    fn test_mirrored_board() {
+        let board = Chessboard::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKB1R w KQq f6 0 3");
+        let mut expected = board;
+        expected.create_position_from_input_string(String::from(
+            "rnbqkb1r/pppp1ppp/8/8/3PpP2/8/PPP1P1PP/RNBQKBNR b Qkq f3 0 3",
+        ));
+        let mirrored = board.mirrored();
+
+        assert_eq!(Color::Black, mirrored.current_move);
+        assert_eq!(Some(29), mirrored.en_passant);
+        assert!(mirrored.castle.white_castle_long && !mirrored.castle.white_castle_short);
+        assert!(mirrored.castle.black_castle_short && mirrored.castle.black_castle_long);
+        assert_eq!(expected.figures, mirrored.figures);
+        assert_eq!(expected.zobrist_key, mirrored.zobrist_key);
+        assert_eq!(board.zobrist_key, mirrored.mirrored().zobrist_key);
+
+        let flipped = board.flipped_horizontal();
+        assert!(!flipped.castle.white_can_castle() && !flipped.castle.black_can_castle());
+        assert_eq!(Some(34), flipped.en_passant);
+        assert_eq!(board.figures[0][0].flip_horizontal(), flipped.figures[0][0]);
     }
 
     #[test]
@@ -913,7 +857,7 @@ mod tests {
         let position =
             String::from("r1k2b1r/p1p1pppp/2p1q1b1/3pN3/3P1B2/2Q1PP2/PPP3PP/R3K2R w KQ - 2 13");
         board.create_position_from_input_string(position);
-        make_move(Vec::new(), &board, &mut Vec::new());
+        make_move(Vec::new(), &board, &[], SearchOptions::default());
         // just count to check if we run into issues with king related zo zobrist
     }
 }
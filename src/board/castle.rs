@@ -25,17 +25,4 @@ impl Castle {
             Color::Black => self.black_can_castle()
         }
     }
-
-    pub fn set_has_castled(&mut self, color: Color){
-        match color{
-            Color::White => {
-                self.white_castle_long = false;
-                self.white_castle_short = false;
-            },
-            Color::Black => {
-                self.black_castle_long = false;
-                self.black_castle_short = false
-            }
-        }
-    }
 }
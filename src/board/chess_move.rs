@@ -0,0 +1,179 @@
+use smallvec::SmallVec;
+
+use super::{bitboard::Bitboard, board::Chessboard, promotion::Promotion};
+
+// 4 bit move types stored in the upper bits of a move
+// promotions have bit 3 set, captures bit 2 (e.g. QueenPromotionCapture = 8 + 4 + 3)
+#[derive(PartialEq, Eq, Clone, Debug, Copy)]
+#[repr(u16)]
+pub enum MoveType {
+    Quiet = 0,
+    DoublePawnPush = 1,
+    KingCastle = 2,
+    QueenCastle = 3,
+    Capture = 4,
+    EnPassant = 5,
+    KnightPromotion = 8,
+    BishopPromotion = 9,
+    RookPromotion = 10,
+    QueenPromotion = 11,
+    KnightPromotionCapture = 12,
+    BishopPromotionCapture = 13,
+    RookPromotionCapture = 14,
+    QueenPromotionCapture = 15,
+}
+
+const SQUARE_MASK: u16 = 0b11_1111;
+const TO_SHIFT: u16 = 6;
+const TYPE_SHIFT: u16 = 12;
+const CAPTURE_FLAG: u16 = 4;
+const PROMOTION_FLAG: u16 = 8;
+
+// 6 bits from, 6 bits to, 4 bits move type
+// a1a1 (all zero) is never a valid move and is used as "no move"
+#[derive(PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub struct Move(u16);
+
+impl Move {
+    pub fn new(from: usize, to: usize, move_type: MoveType) -> Move {
+        Move(from as u16 | (to as u16) << TO_SHIFT | (move_type as u16) << TYPE_SHIFT)
+    }
+
+    pub fn promotion(from: usize, to: usize, promoted_to: Promotion, is_capture: bool) -> Move {
+        let move_type = match (promoted_to, is_capture) {
+            (Promotion::Knight, false) => MoveType::KnightPromotion,
+            (Promotion::Bishop, false) => MoveType::BishopPromotion,
+            (Promotion::Rook, false) => MoveType::RookPromotion,
+            (Promotion::Queen, false) => MoveType::QueenPromotion,
+            (Promotion::Knight, true) => MoveType::KnightPromotionCapture,
+            (Promotion::Bishop, true) => MoveType::BishopPromotionCapture,
+            (Promotion::Rook, true) => MoveType::RookPromotionCapture,
+            (Promotion::Queen, true) => MoveType::QueenPromotionCapture,
+        };
+        Move::new(from, to, move_type)
+    }
+
+    pub fn from(&self) -> usize {
+        (self.0 & SQUARE_MASK) as usize
+    }
+
+    pub fn to(&self) -> usize {
+        (self.0 >> TO_SHIFT & SQUARE_MASK) as usize
+    }
+
+    fn flags(&self) -> u16 {
+        self.0 >> TYPE_SHIFT
+    }
+
+    pub fn move_type(&self) -> MoveType {
+        match self.flags() {
+            1 => MoveType::DoublePawnPush,
+            2 => MoveType::KingCastle,
+            3 => MoveType::QueenCastle,
+            4 => MoveType::Capture,
+            5 => MoveType::EnPassant,
+            8 => MoveType::KnightPromotion,
+            9 => MoveType::BishopPromotion,
+            10 => MoveType::RookPromotion,
+            11 => MoveType::QueenPromotion,
+            12 => MoveType::KnightPromotionCapture,
+            13 => MoveType::BishopPromotionCapture,
+            14 => MoveType::RookPromotionCapture,
+            15 => MoveType::QueenPromotionCapture,
+            _ => MoveType::Quiet,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.0 == 0
+    }
+
+    // en passant included
+    pub fn is_capture(&self) -> bool {
+        self.flags() & CAPTURE_FLAG != 0
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        self.flags() & PROMOTION_FLAG != 0
+    }
+
+    pub fn is_castle(&self) -> bool {
+        matches!(self.move_type(), MoveType::KingCastle | MoveType::QueenCastle)
+    }
+
+    pub fn promoted_to(&self) -> Option<Promotion> {
+        if !self.is_promotion() {
+            return None;
+        }
+        match self.flags() & 0b11 {
+            0 => Some(Promotion::Knight),
+            1 => Some(Promotion::Bishop),
+            2 => Some(Promotion::Rook),
+            _ => Some(Promotion::Queen),
+        }
+    }
+}
+
+// used if the targets can contain empty and opponent fields (e.g. preventing a check)
+pub fn add_quiet_moves_and_captures(
+    board: &Chessboard,
+    from: usize,
+    targets: Bitboard,
+    moves: &mut SmallVec<[Move; 64]>,
+) {
+    targets.iterate_board(|to| {
+        let move_type = if board.get_opponents().field_is_used(to) {
+            MoveType::Capture
+        } else {
+            MoveType::Quiet
+        };
+        moves.push(Move::new(from, to, move_type))
+    });
+}
+
+impl std::fmt::Debug for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Move({} -> {} {:?})", self.from(), self.to(), self.move_type())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        let mov = Move::new(12, 28, MoveType::DoublePawnPush);
+        assert_eq!(12, mov.from());
+        assert_eq!(28, mov.to());
+        assert_eq!(MoveType::DoublePawnPush, mov.move_type());
+        assert!(!mov.is_capture());
+        assert!(!mov.is_promotion());
+
+        let mov = Move::new(63, 0, MoveType::Capture);
+        assert_eq!(63, mov.from());
+        assert_eq!(0, mov.to());
+        assert!(mov.is_capture());
+        assert!(!mov.is_null());
+
+        assert!(Move::new(36, 43, MoveType::EnPassant).is_capture());
+        assert!(Move::new(4, 6, MoveType::KingCastle).is_castle());
+        assert!(Move::default().is_null());
+        assert_eq!(2, std::mem::size_of::<Move>());
+    }
+
+    #[test]
+    fn test_promotions() {
+        for promotion in [Promotion::Knight, Promotion::Bishop, Promotion::Rook, Promotion::Queen] {
+            for is_capture in [false, true] {
+                let mov = Move::promotion(53, 61, promotion, is_capture);
+                assert_eq!(53, mov.from());
+                assert_eq!(61, mov.to());
+                assert_eq!(Some(promotion), mov.promoted_to());
+                assert_eq!(is_capture, mov.is_capture());
+                assert!(mov.is_promotion());
+            }
+        }
+        assert_eq!(None, Move::new(8, 16, MoveType::Quiet).promoted_to());
+    }
+}
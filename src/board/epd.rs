@@ -0,0 +1,332 @@
+use std::{
+    fs::File,
+    io::{self, BufRead},
+    path::Path,
+};
+
+use super::board::Chessboard;
+
+// number of perft results (D1..D6) we keep track of
+pub const PERFT_DEPTHS: usize = 6;
+
+// Extended Position Description - four FEN fields followed by opcodes
+// e.g. r1b1k2r/.../R3K2R w KQkq - bm Qxf7+; id "WAC.001";
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Epd {
+    // placement, side to move, castling and en passant
+    pub position: String,
+    // bm - moves are kept as they are written in the file (SAN)
+    pub best_moves: Vec<String>,
+    // am
+    pub avoid_moves: Vec<String>,
+    pub id: Option<String>,
+    // c0 - c9
+    pub comments: [Option<String>; 10],
+    // ce - centipawn evaluation from side to move pov
+    pub centipawn_evaluation: Option<i32>,
+    // acd - analysis count depth
+    pub analysis_depth: Option<u32>,
+    // pv
+    pub principal_variation: Vec<String>,
+    // D1 - D6 perft node counts, index 0 is depth 1
+    pub perft: [Option<u64>; PERFT_DEPTHS],
+    // opcodes we do not know are kept to be able to write them back
+    pub other: Vec<(String, Vec<String>)>,
+}
+
+impl Epd {
+    pub fn parse(line: &str) -> Option<Epd> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        // first four fields are the position - everything after are operations
+        let mut operations = line;
+        let mut position_fields = Vec::new();
+        for _ in 0..4 {
+            operations = operations.trim_start();
+            let field_end = operations.find(char::is_whitespace).unwrap_or(operations.len());
+            if field_end == 0 {
+                return None;
+            }
+            position_fields.push(&operations[..field_end]);
+            operations = &operations[field_end..];
+        }
+        if position_fields[0].split('/').count() != 8 {
+            return None;
+        }
+        let mut epd = Epd {
+            position: position_fields.join(" "),
+            ..Default::default()
+        };
+
+        for (opcode, operands) in split_operations(operations)? {
+            epd.add_operation(opcode, operands)?;
+        }
+        Some(epd)
+    }
+
+    fn add_operation(&mut self, opcode: String, operands: Vec<String>) -> Option<()> {
+        match opcode.as_str() {
+            "bm" => self.best_moves = operands,
+            "am" => self.avoid_moves = operands,
+            "pv" => self.principal_variation = operands,
+            "id" => self.id = Some(operands.join(" ")),
+            "ce" => self.centipawn_evaluation = Some(operands.first()?.parse().ok()?),
+            "acd" => self.analysis_depth = Some(operands.first()?.parse().ok()?),
+            _ => {
+                if let Some(index) = get_comment_index(&opcode) {
+                    self.comments[index] = Some(operands.join(" "));
+                } else if let Some(depth) = get_perft_depth(&opcode) {
+                    self.perft[depth - 1] = Some(operands.first()?.parse().ok()?);
+                } else {
+                    self.other.push((opcode, operands));
+                }
+            }
+        }
+        Some(())
+    }
+
+    // bench writes its results with it (acd, ce, pv)
+    pub fn to_epd_string(&self) -> String {
+        let mut epd = self.position.clone();
+        add_operation_to_string(&mut epd, "bm", &self.best_moves);
+        add_operation_to_string(&mut epd, "am", &self.avoid_moves);
+        if let Some(id) = &self.id {
+            add_operation_to_string(&mut epd, "id", &[quote(id)]);
+        }
+        if let Some(evaluation) = self.centipawn_evaluation {
+            add_operation_to_string(&mut epd, "ce", &[evaluation.to_string()]);
+        }
+        if let Some(depth) = self.analysis_depth {
+            add_operation_to_string(&mut epd, "acd", &[depth.to_string()]);
+        }
+        add_operation_to_string(&mut epd, "pv", &self.principal_variation);
+        for (index, comment) in self.comments.iter().enumerate() {
+            if let Some(comment) = comment {
+                add_operation_to_string(&mut epd, &format!("c{}", index), &[quote(comment)]);
+            }
+        }
+        for (index, nodes) in self.perft.iter().enumerate() {
+            if let Some(nodes) = nodes {
+                add_operation_to_string(&mut epd, &format!("D{}", index + 1), &[nodes.to_string()]);
+            }
+        }
+        for (opcode, operands) in self.other.iter() {
+            // keep operands with spaces together
+            let operands: Vec<String> = operands
+                .iter()
+                .map(|x| if x.contains(' ') { quote(x) } else { x.clone() })
+                .collect();
+            add_operation_to_string(&mut epd, opcode, &operands);
+        }
+        epd
+    }
+
+    pub fn create_board(&self) -> Chessboard {
+        let mut board = Chessboard {
+            ..Default::default()
+        };
+        board.create_position_from_input_string(self.position.clone());
+        board
+    }
+
+    // deepest depth with known perft result
+    pub fn max_perft_depth(&self) -> Option<u8> {
+        self.perft
+            .iter()
+            .rposition(|x| x.is_some())
+            .map(|index| index as u8 + 1)
+    }
+}
+
+// returns all valid EPD lines from a file (empty lines and comments are skipped)
+pub fn read_epd_file<P>(filename: P) -> io::Result<Vec<Epd>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(filename)?;
+    let lines = io::BufReader::new(file).lines();
+    Ok(lines
+        .map_while(Result::ok)
+        .filter_map(|line| Epd::parse(&line))
+        .collect())
+}
+
+// used for the suites that are included into the binary/tests
+pub fn parse_epd_suite(suite: &str) -> Vec<Epd> {
+    suite.lines().filter_map(Epd::parse).collect()
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value)
+}
+
+fn add_operation_to_string(epd: &mut String, opcode: &str, operands: &[String]) {
+    if operands.is_empty() {
+        return;
+    }
+    epd.push(' ');
+    epd.push_str(opcode);
+    for operand in operands {
+        epd.push(' ');
+        epd.push_str(operand);
+    }
+    epd.push(';');
+}
+
+fn get_comment_index(opcode: &str) -> Option<usize> {
+    let index = opcode.strip_prefix('c')?.parse::<usize>().ok()?;
+    if opcode.len() == 2 && index < 10 {
+        return Some(index);
+    }
+    None
+}
+
+fn get_perft_depth(opcode: &str) -> Option<usize> {
+    let depth = opcode.strip_prefix('D')?.parse::<usize>().ok()?;
+    if (1..=PERFT_DEPTHS).contains(&depth) {
+        return Some(depth);
+    }
+    None
+}
+
+// split "bm e4 d4; id "name; with semicolon";" into opcodes with their operands
+fn split_operations(operations: &str) -> Option<Vec<(String, Vec<String>)>> {
+    let mut result = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in operations.chars() {
+        match c {
+            '"' => {
+                // closing quotes end the token - even if it is an empty string
+                if in_quotes {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+                in_quotes = !in_quotes;
+            }
+            _ if in_quotes => current.push(c),
+            ';' => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+                if !tokens.is_empty() {
+                    let opcode = tokens.remove(0);
+                    result.push((opcode, tokens.clone()));
+                    tokens.clear();
+                }
+            }
+            _ if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    // unterminated string or operation without semicolon
+    if in_quotes || !current.is_empty() || !tokens.is_empty() {
+        return None;
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{engine::perft::perft, figures::color::Color};
+
+    #[test]
+    fn test_parse_test_suite_line() {
+        let epd = Epd::parse(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+        )
+        .unwrap();
+        assert_eq!(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - -",
+            epd.position
+        );
+        assert_eq!(vec![String::from("Qg6")], epd.best_moves);
+        assert_eq!(Some(String::from("WAC.001")), epd.id);
+        assert!(epd.avoid_moves.is_empty());
+    }
+
+    #[test]
+    fn test_parse_all_opcodes() {
+        let epd = Epd::parse(
+            "4k3/8/8/8/8/8/4P3/4K3 w - - am Kd1 Kf1; bm e4; ce -35; acd 12; pv e4 Kd7 Kd2; c0 \"comment; with semicolon\"; c9 \"last\"; D1 7; D3 310; hmvc 0;",
+        )
+        .unwrap();
+        assert_eq!(vec![String::from("Kd1"), String::from("Kf1")], epd.avoid_moves);
+        assert_eq!(vec![String::from("e4")], epd.best_moves);
+        assert_eq!(Some(-35), epd.centipawn_evaluation);
+        assert_eq!(Some(12), epd.analysis_depth);
+        assert_eq!(3, epd.principal_variation.len());
+        assert_eq!(Some(String::from("comment; with semicolon")), epd.comments[0]);
+        assert_eq!(Some(String::from("last")), epd.comments[9]);
+        assert_eq!([Some(7), None, Some(310), None, None, None], epd.perft);
+        assert_eq!(Some(3), epd.max_perft_depth());
+        assert_eq!(
+            vec![(String::from("hmvc"), vec![String::from("0")])],
+            epd.other
+        );
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        assert_eq!(None, Epd::parse(""));
+        assert_eq!(None, Epd::parse("# comment"));
+        assert_eq!(None, Epd::parse("4k3/8/8 w - -"));
+        // missing semicolon and unterminated string
+        assert_eq!(None, Epd::parse("4k3/8/8/8/8/8/4P3/4K3 w - - bm e4"));
+        assert_eq!(None, Epd::parse("4k3/8/8/8/8/8/4P3/4K3 w - - id \"abc;"));
+        assert_eq!(None, Epd::parse("4k3/8/8/8/8/8/4P3/4K3 w - - ce abc;"));
+    }
+
+    #[test]
+    fn test_write_and_parse_again() {
+        let line = "4k3/8/8/8/8/8/4P3/4K3 b - - bm Kd7; id \"test position\"; ce 20; c1 \"a comment\"; D1 5; D2 38;";
+        let epd = Epd::parse(line).unwrap();
+        assert_eq!(line, epd.to_epd_string());
+        assert_eq!(epd, Epd::parse(&epd.to_epd_string()).unwrap());
+    }
+
+    #[test]
+    fn test_create_board() {
+        let epd = Epd::parse("4k3/8/8/8/8/8/4P3/4K3 b - - D1 5;").unwrap();
+        let board = epd.create_board();
+        assert_eq!(Color::Black, board.current_move);
+        assert_eq!(5, perft(&board, 1, None));
+    }
+
+    #[test]
+    fn test_perft_suite_shallow() {
+        for epd in parse_epd_suite(include_str!("../../suites/perft.epd")) {
+            let board = epd.create_board();
+            for depth in 1..=2 {
+                if let Some(expected) = epd.perft[depth - 1] {
+                    assert_eq!(expected, perft(&board, depth as u8, None), "{}", epd.position);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_perft_suite() {
+        for epd in parse_epd_suite(include_str!("../../suites/perft.epd")) {
+            let board = epd.create_board();
+            for depth in 1..=PERFT_DEPTHS {
+                // deeper depths take way to long for debug builds
+                if let Some(expected) = epd.perft[depth - 1].filter(|nodes| *nodes <= 5_000_000) {
+                    assert_eq!(expected, perft(&board, depth as u8, None), "{}", epd.position);
+                }
+            }
+        }
+    }
+}
@@ -2,4 +2,7 @@ pub mod board;
 pub mod promotion;
 pub mod bitboard;
 pub mod castle;
-pub mod fen;
\ No newline at end of file
+pub mod chess_move;
+pub mod fen;
+pub mod square;
+pub mod epd;
\ No newline at end of file
@@ -25,7 +25,8 @@ pub fn convert_input_string_to_promotion(promotion_str: Option<&str>) -> Option<
 
     match promotion_string{
         "q" | "Q" => Some(Promotion::Queen),
-        "k" | "K" => Some(Promotion::Knight),
+        // k was used for knights before - keep it for older inputs
+        "n" | "N" | "k" | "K" => Some(Promotion::Knight),
         "b" | "B" => Some(Promotion::Bishop),
         "r" | "R" => Some(Promotion::Rook),
         _ => None
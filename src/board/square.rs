@@ -0,0 +1,332 @@
+use std::{fmt, str::FromStr};
+
+use crate::figures::color::Color;
+
+use super::bitboard::Bitboard;
+
+const FILE_A_MASK: u64 = 0x0101_0101_0101_0101;
+const RANK_1_MASK: u64 = 0xFF;
+// a1 - h8
+const DIAGONAL_MASK: u64 = 0x8040_2010_0804_0201;
+// h1 - a8
+const ANTI_DIAGONAL_MASK: u64 = 0x0102_0408_1020_4080;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ParseSquareError;
+
+// column of the board - a = 0 .. h = 7
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct File(u8);
+
+impl File {
+    pub const A: File = File(0);
+    pub const B: File = File(1);
+    pub const C: File = File(2);
+    pub const D: File = File(3);
+    pub const E: File = File(4);
+    pub const F: File = File(5);
+    pub const G: File = File(6);
+    pub const H: File = File(7);
+
+    pub fn new(index: usize) -> File {
+        debug_assert!(index < 8, "file {} is out of bounds", index);
+        File(index as u8)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn offset(&self, delta: i8) -> Option<File> {
+        let file = self.0 as i8 + delta;
+        (0..8).contains(&file).then_some(File(file as u8))
+    }
+
+    // a <-> h
+    pub fn mirror(&self) -> File {
+        File(7 - self.0)
+    }
+
+    pub fn mask(&self) -> Bitboard {
+        Bitboard {
+            board: FILE_A_MASK << self.0,
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<File> {
+        match c {
+            'a'..='h' => Some(File(c as u8 - b'a')),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        (b'a' + self.0) as char
+    }
+}
+
+impl FromStr for File {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => File::from_char(c).ok_or(ParseSquareError),
+            _ => Err(ParseSquareError),
+        }
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// row of the board - first rank = 0 .. eighth rank = 7
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Rank(u8);
+
+impl Rank {
+    pub const FIRST: Rank = Rank(0);
+    pub const SECOND: Rank = Rank(1);
+    pub const THIRD: Rank = Rank(2);
+    pub const FOURTH: Rank = Rank(3);
+    pub const FIFTH: Rank = Rank(4);
+    pub const SIXTH: Rank = Rank(5);
+    pub const SEVENTH: Rank = Rank(6);
+    pub const EIGHTH: Rank = Rank(7);
+
+    pub fn new(index: usize) -> Rank {
+        debug_assert!(index < 8, "rank {} is out of bounds", index);
+        Rank(index as u8)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn offset(&self, delta: i8) -> Option<Rank> {
+        let rank = self.0 as i8 + delta;
+        (0..8).contains(&rank).then_some(Rank(rank as u8))
+    }
+
+    // 1 <-> 8
+    pub fn mirror(&self) -> Rank {
+        Rank(7 - self.0)
+    }
+
+    // rank as seen by the given color, e.g. the second rank of black is the seventh rank
+    pub fn relative_to(&self, color: Color) -> Rank {
+        match color {
+            Color::White => *self,
+            Color::Black => self.mirror(),
+        }
+    }
+
+    pub fn mask(&self) -> Bitboard {
+        Bitboard {
+            board: RANK_1_MASK << (self.0 * 8),
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Rank> {
+        match c {
+            '1'..='8' => Some(Rank(c as u8 - b'1')),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        (b'1' + self.0) as char
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Rank::from_char(c).ok_or(ParseSquareError),
+            _ => Err(ParseSquareError),
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// a1 = 0, h1 = 7, a8 = 56, h8 = 63 - same as the fields of a bitboard
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct Square(u8);
+
+impl Square {
+    pub fn new(file: File, rank: Rank) -> Square {
+        Square(rank.0 * 8 + file.0)
+    }
+
+    pub fn from_index(index: usize) -> Square {
+        debug_assert!(index < 64, "square {} is out of bounds", index);
+        Square(index as u8)
+    }
+
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn file(&self) -> File {
+        File(self.0 % 8)
+    }
+
+    pub fn rank(&self) -> Rank {
+        Rank(self.0 / 8)
+    }
+
+    // None if we would leave the board (no wrapping from h to a)
+    pub fn offset(&self, file_delta: i8, rank_delta: i8) -> Option<Square> {
+        Some(Square::new(
+            self.file().offset(file_delta)?,
+            self.rank().offset(rank_delta)?,
+        ))
+    }
+
+    // flip along the horizontal axis - a1 <-> a8 (used to swap the colors of a position)
+    pub fn mirror(&self) -> Square {
+        Square(self.0 ^ 56)
+    }
+
+    // flip along the vertical axis - a1 <-> h1
+    pub fn flip_horizontal(&self) -> Square {
+        Square(self.0 ^ 7)
+    }
+
+    // square as seen by the given color, e.g. e2 of black is e7
+    pub fn relative_to(&self, color: Color) -> Square {
+        match color {
+            Color::White => *self,
+            Color::Black => self.mirror(),
+        }
+    }
+
+    pub fn mask(&self) -> Bitboard {
+        Bitboard {
+            board: 1 << self.0,
+        }
+    }
+
+    // diagonal going through the square from bottom left to top right
+    pub fn diagonal_mask(&self) -> Bitboard {
+        let offset = self.file().index() as i32 - self.rank().index() as i32;
+        let board = if offset > 0 {
+            DIAGONAL_MASK >> (8 * offset)
+        } else {
+            DIAGONAL_MASK << (-8 * offset)
+        };
+        Bitboard { board }
+    }
+
+    // diagonal going through the square from bottom right to top left
+    pub fn anti_diagonal_mask(&self) -> Bitboard {
+        let offset = (self.file().index() + self.rank().index()) as i32 - 7;
+        let board = if offset > 0 {
+            ANTI_DIAGONAL_MASK << (8 * offset)
+        } else {
+            ANTI_DIAGONAL_MASK >> (-8 * offset)
+        };
+        Bitboard { board }
+    }
+}
+
+impl From<Square> for usize {
+    fn from(square: Square) -> usize {
+        square.index()
+    }
+}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => Ok(Square::new(
+                File::from_char(file).ok_or(ParseSquareError)?,
+                Rank::from_char(rank).ok_or(ParseSquareError)?,
+            )),
+            _ => Err(ParseSquareError),
+        }
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            assert_eq!(Ok(square), square.to_string().parse());
+        }
+        assert_eq!(Ok(Square::from_index(0)), "a1".parse());
+        assert_eq!(Ok(Square::from_index(27)), "d4".parse());
+        assert_eq!(Ok(Square::from_index(63)), "h8".parse());
+        assert_eq!("g5", Square::from_index(38).to_string());
+
+        assert_eq!(Err(ParseSquareError), "i1".parse::<Square>());
+        assert_eq!(Err(ParseSquareError), "a9".parse::<Square>());
+        assert_eq!(Err(ParseSquareError), "a".parse::<Square>());
+        assert_eq!(Err(ParseSquareError), "a1a".parse::<Square>());
+        assert_eq!(Ok(File::E), "e".parse());
+        assert_eq!(Ok(Rank::FOURTH), "4".parse());
+    }
+
+    #[test]
+    fn test_offsets() {
+        let e4 = Square::new(File::E, Rank::FOURTH);
+        assert_eq!(Some(Square::new(File::F, Rank::SIXTH)), e4.offset(1, 2));
+        assert_eq!(Some(Square::new(File::A, Rank::FIRST)), e4.offset(-4, -3));
+        assert_eq!(None, e4.offset(4, 0));
+        assert_eq!(None, e4.offset(0, -4));
+        // no wrapping around the board
+        assert_eq!(None, Square::from_index(7).offset(1, 0));
+        assert_eq!(None, Square::from_index(8).offset(-1, 0));
+    }
+
+    #[test]
+    fn test_mirroring() {
+        let b2 = Square::new(File::B, Rank::SECOND);
+        assert_eq!(Square::new(File::B, Rank::SEVENTH), b2.mirror());
+        assert_eq!(Square::new(File::G, Rank::SECOND), b2.flip_horizontal());
+        assert_eq!(b2.mirror(), b2.relative_to(Color::Black));
+        assert_eq!(Rank::SEVENTH, Rank::SECOND.relative_to(Color::Black));
+        assert_eq!(File::H, File::A.mirror());
+    }
+
+    #[test]
+    fn test_masks() {
+        assert_eq!(0x0101_0101_0101_0101, File::A.mask().board);
+        assert_eq!(0x8080_8080_8080_8080, File::H.mask().board);
+        assert_eq!(0xFF00, Rank::SECOND.mask().board);
+        assert_eq!(0xFF00_0000_0000_0000, Rank::EIGHTH.mask().board);
+        assert_eq!(1 << 28, Square::new(File::E, Rank::FOURTH).mask().board);
+
+        // b1 - h7 and a2 - b1
+        let b1 = Square::new(File::B, Rank::FIRST);
+        assert_eq!(vec![1, 10, 19, 28, 37, 46, 55], b1.diagonal_mask().collect::<Vec<usize>>());
+        assert_eq!(vec![1, 8], b1.anti_diagonal_mask().collect::<Vec<usize>>());
+        assert_eq!(DIAGONAL_MASK, Square::from_index(27).diagonal_mask().board);
+        assert_eq!(ANTI_DIAGONAL_MASK, Square::from_index(28).anti_diagonal_mask().board);
+        assert_eq!(vec![56], Square::from_index(56).diagonal_mask().collect::<Vec<usize>>());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    board::{
+        bitboard::{between, Bitboard},
+        board::Chessboard,
+    },
+    figures::{
+        color::Color,
+        king::get_fields_threatened_by_king,
+        knight::get_fields_threatened_by_knight,
+        pawn::get_fields_threatened_by_pawn,
+        piece::Piece,
+        sliding_moves::{get_bishop_attacks, get_rook_attacks},
+    },
+    KING_MOVES, KNIGHT_MOVES, PAWN_THREATS,
+};
+
+// pieces of both colors attacking the field - sliders are blocked by the given occupancy
+pub fn attackers_to(board: &Chessboard, position: usize, occupied: Bitboard) -> Bitboard {
+    // a white pawn attacks the field if a black pawn on the field would attack the pawn
+    let white_pawns = PAWN_THREATS[Color::Black as usize][position] & *board.get_pieces(Color::White, Piece::Pawn);
+    let black_pawns = PAWN_THREATS[Color::White as usize][position] & *board.get_pieces(Color::Black, Piece::Pawn);
+    let knights = KNIGHT_MOVES[position] & get_both_colors(board, Piece::Knight);
+    let kings = KING_MOVES[position] & get_both_colors(board, Piece::King);
+    white_pawns | black_pawns | knights | kings | slider_attackers_to(board, position, occupied)
+}
+
+// rooks, bishops and queens of both colors attacking the field (e.g. to find x-rays after removing a piece)
+pub fn slider_attackers_to(board: &Chessboard, position: usize, occupied: Bitboard) -> Bitboard {
+    let queens = get_both_colors(board, Piece::Queen);
+    (get_rook_attacks(position, occupied) & (get_both_colors(board, Piece::Rook) | queens))
+        | (get_bishop_attacks(position, occupied) & (get_both_colors(board, Piece::Bishop) | queens))
+}
+
+fn get_both_colors(board: &Chessboard, piece: Piece) -> Bitboard {
+    *board.get_pieces(Color::White, piece) | *board.get_pieces(Color::Black, piece)
+}
+
+// all fields attacked by the color (no matter which piece stands on them)
+pub fn get_attacked_fields(board: &Chessboard, color: Color, occupied: Bitboard) -> Bitboard {
+    let mut attacked = Bitboard::new();
+    for position in *board.get_pieces(color, Piece::Pawn) {
+        attacked |= get_fields_threatened_by_pawn(position, color);
+    }
+    for position in *board.get_pieces(color, Piece::Knight) {
+        attacked |= get_fields_threatened_by_knight(position);
+    }
+    let queens = *board.get_pieces(color, Piece::Queen);
+    for position in *board.get_pieces(color, Piece::Bishop) | queens {
+        attacked |= get_bishop_attacks(position, occupied);
+    }
+    for position in *board.get_pieces(color, Piece::Rook) | queens {
+        attacked |= get_rook_attacks(position, occupied);
+    }
+    for position in *board.get_pieces(color, Piece::King) {
+        attacked |= get_fields_threatened_by_king(position);
+    }
+    attacked
+}
+
+// opponent pieces giving check to the king of the side to move
+pub fn get_checkers(board: &Chessboard, king_position: usize) -> Bitboard {
+    attackers_to(board, king_position, board.positions) & *board.get_opponents()
+}
+
+// our pieces standing alone between our king and an opponent rook, bishop or queen
+pub fn get_pinned_pieces(board: &Chessboard, king_position: usize) -> Bitboard {
+    get_king_blockers(board, king_position, board.get_opponent_color()) & *board.get_positions_by_current_player()
+}
+
+// pieces of both colors standing alone between the king and a rook, bishop or queen of the given color
+// our pieces in front of the opponent king can give a discovered check
+pub fn get_king_blockers(board: &Chessboard, king_position: usize, sniper_color: Color) -> Bitboard {
+    let queens = *board.get_pieces(sniper_color, Piece::Queen);
+    // sliders which would attack the king on an empty board
+    let snipers = (get_rook_attacks(king_position, Bitboard::new())
+        & (*board.get_pieces(sniper_color, Piece::Rook) | queens))
+        | (get_bishop_attacks(king_position, Bitboard::new())
+            & (*board.get_pieces(sniper_color, Piece::Bishop) | queens));
+
+    let mut blockers = Bitboard::new();
+    for sniper in snipers {
+        let between_fields = between(king_position, sniper) & board.positions;
+        if between_fields.len() == 1 {
+            blockers |= between_fields;
+        }
+    }
+    blockers
+}
+
+// fields from which a piece of the side to move would give check to the king (by piece)
+pub fn get_check_fields(board: &Chessboard, king_position: usize) -> [Bitboard; 6] {
+    let bishop = get_bishop_attacks(king_position, board.positions);
+    let rook = get_rook_attacks(king_position, board.positions);
+    [
+        // our pawn gives check where a pawn of the opponent on the kings field would attack it
+        PAWN_THREATS[board.get_opponent_color() as usize][king_position],
+        KNIGHT_MOVES[king_position],
+        bishop,
+        rook,
+        bishop | rook,
+        Bitboard::new(),
+    ]
+}
+
+// fields a piece (not the king) has to move to, to stop the check - capture the checker or block a slider
+// every field if we are not in check, no field in double check
+pub fn get_check_block_mask(king_position: usize, checkers: Bitboard) -> Bitboard {
+    match checkers.len() {
+        0 => !Bitboard::new(),
+        1 => checkers | between(king_position, checkers.get_first_field()),
+        _ => Bitboard::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::chess_move::{Move, MoveType};
+
+    use super::*;
+
+    #[test]
+    fn test_pinned_pieces() {
+        let mut board = Chessboard {
+            ..Default::default()
+        };
+        // move white queen up on diagonal
+        board.move_figure(Move::new(3, 33, MoveType::Quiet));
+        // move black pawn forward
+        board.move_figure(Move::new(51, 35, MoveType::DoublePawnPush));
+        // white rook to pin center
+        board.move_figure(Move::new(0, 20, MoveType::Quiet));
+        // black knight into pin of queen
+        board.move_figure(Move::new(57, 42, MoveType::Quiet));
+        // white dummy move to give the move to black
+        board.move_figure(Move::new(8, 16, MoveType::Quiet));
+
+        // e pawn and knight on 42
+        let pinned = get_pinned_pieces(&board, 60);
+        assert_eq!(vec![42, 52], pinned.get_used_fields());
+
+        // no pins in the start position
+        assert!(get_pinned_pieces(&Chessboard::default(), 4).is_empty());
+        // two pieces between king and rook - none of them is pinned
+        let board = Chessboard::from_fen("4r1k1/8/8/8/4p3/4P3/8/4K3 w - - 0 1");
+        assert!(get_pinned_pieces(&board, 4).is_empty());
+        // opponent pieces in between are not pinned
+        let board = Chessboard::from_fen("4r1k1/8/8/8/4p3/8/8/4K3 w - - 0 1");
+        assert!(get_pinned_pieces(&board, 4).is_empty());
+    }
+
+    #[test]
+    fn test_checkers_and_check_block_mask() {
+        // rook check - capture the rook or block on the two fields in between
+        let board = Chessboard::from_fen("6k1/8/8/8/8/8/8/r2K4 w - - 0 1");
+        let checkers = get_checkers(&board, 3);
+        assert_eq!(vec![0], checkers.get_used_fields());
+        assert_eq!(vec![0, 1, 2], get_check_block_mask(3, checkers).get_used_fields());
+
+        // bishop check
+        let board = Chessboard::from_fen("6k1/7b/8/8/8/3K4/8/8 w - - 0 1");
+        let checkers = get_checkers(&board, 19);
+        assert_eq!(vec![28, 37, 46, 55], get_check_block_mask(19, checkers).get_used_fields());
+
+        // pawn and knight checks can only be captured
+        let board = Chessboard::from_fen("6k1/8/8/8/2p5/3K4/8/8 w - - 0 1");
+        assert_eq!(vec![26], get_check_block_mask(19, get_checkers(&board, 19)).get_used_fields());
+        let board = Chessboard::from_fen("6k1/8/8/8/8/2N5/k7/8 b - - 0 1");
+        assert_eq!(vec![18], get_check_block_mask(8, get_checkers(&board, 8)).get_used_fields());
+
+        // double check - only the king can move
+        let board = Chessboard::from_fen("6k1/8/8/8/8/4n3/8/r2K4 w - - 0 1");
+        assert_eq!(2, get_checkers(&board, 3).len());
+        assert!(get_check_block_mask(3, get_checkers(&board, 3)).is_empty());
+    }
+
+    #[test]
+    fn test_attackers_to() {
+        let board = Chessboard::from_fen("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1");
+        // e5 - only the white rook, the black rook on d8 does not see it
+        assert_eq!(vec![4], attackers_to(&board, 36, board.positions).get_used_fields());
+        // king and rook defend c8 - the black pawns only attack downwards
+        assert_eq!(vec![57, 59], attackers_to(&board, 58, board.positions).get_used_fields());
+    }
+}
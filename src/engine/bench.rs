@@ -0,0 +1,115 @@
+use std::{fs, time::SystemTime};
+
+use crate::{
+    board::{
+        board::Chessboard,
+        chess_move::Move,
+        epd::{parse_epd_suite, read_epd_file, Epd},
+    },
+    helper::pgn::convert_move_to_san,
+    TRANSPOSITION_TABLE,
+};
+
+use super::engine::{search_for_best_move, SearchOptions, TimeForMove};
+
+// bench [time <ms>] [epd <file>] [out <file>] [recapture] [pawnpush] - depth reached in a fixed time for every position of the suite
+// compare the average depth before and after changing the search (e.g. the reduction parameters or the optional extensions)
+// out writes the suite with the results of the search (acd, ce and pv) as EPD
+pub fn run_bench(commands: &[&str]) {
+    let time_for_move = get_option(commands, "time").unwrap_or(3000);
+    let options = SearchOptions {
+        recapture_extension: commands.contains(&"recapture"),
+        pawn_push_extension: commands.contains(&"pawnpush"),
+    };
+    let suite = match commands.iter().position(|x| *x == "epd").and_then(|index| commands.get(index + 1)) {
+        Some(file) => match read_epd_file(file) {
+            Ok(suite) => suite,
+            Err(error) => {
+                println!("could not read {}: {}", file, error);
+                return;
+            }
+        },
+        None => parse_epd_suite(include_str!("../../suites/bench.epd")),
+    };
+    let analysed = run_bench_suite(&suite, time_for_move, options);
+    if let Some(file) = commands.iter().position(|x| *x == "out").and_then(|index| commands.get(index + 1)) {
+        let lines: String = analysed.iter().map(|epd| epd.to_epd_string() + "\n").collect();
+        if let Err(error) = fs::write(file, lines) {
+            println!("could not write {}: {}", file, error);
+        }
+    }
+}
+
+fn get_option(commands: &[&str], key: &str) -> Option<u64> {
+    let index = commands.iter().position(|x| *x == key)?;
+    commands.get(index + 1)?.parse().ok()
+}
+
+// returns the positions with the depth, rating and pv of the search
+fn run_bench_suite(suite: &[Epd], time_for_move: u64, options: SearchOptions) -> Vec<Epd> {
+    let now = SystemTime::now();
+    let mut depths = Vec::new();
+    let mut solved = Vec::new();
+    let mut analysed = Vec::new();
+    for epd in suite {
+        // every position starts with an empty table - otherwise the order of the suite matters
+        TRANSPOSITION_TABLE.clear();
+        let board = epd.create_board();
+        let (mov, rating, depth, pv) = search_for_best_move(TimeForMove::fixed(time_for_move), &board, false, &[], options);
+        let name = epd.id.clone().unwrap_or(epd.position.clone());
+        // tactical positions (bm / am) - the move found is checked as well
+        if epd.best_moves.is_empty() && epd.avoid_moves.is_empty() {
+            println!("{}: depth {}", name, depth);
+        } else {
+            let san = convert_move_to_san(&board, mov);
+            let is_solved = is_solution(epd, &san);
+            println!("{}: depth {} {} {}", name, depth, san, if is_solved { "solved" } else { "failed" });
+            solved.push(is_solved);
+        }
+        depths.push(depth);
+        analysed.push(Epd {
+            analysis_depth: Some(depth as u32),
+            centipawn_evaluation: Some((rating * 100.0).round() as i32),
+            principal_variation: get_san_line(&board, &pv),
+            ..epd.clone()
+        });
+    }
+    println!();
+    let total: u32 = depths.iter().map(|depth| *depth as u32).sum();
+    println!(
+        "{} positions - {}ms each - average depth {:.2}",
+        suite.len(),
+        time_for_move,
+        total as f32 / depths.len().max(1) as f32
+    );
+    if !solved.is_empty() {
+        println!(
+            "solved {} of {} tactical positions",
+            solved.iter().filter(|is_solved| **is_solved).count(),
+            solved.len()
+        );
+    }
+    println!("took: {:?}", now.elapsed().unwrap_or_default());
+    analysed
+}
+
+fn get_san_line(board: &Chessboard, line: &[Move]) -> Vec<String> {
+    let mut board = *board;
+    line.iter()
+        .map(|mov| {
+            let san = convert_move_to_san(&board, *mov);
+            board.move_figure(*mov);
+            san
+        })
+        .collect()
+}
+
+// the suites write checks and mates (+ / #) not always - they are ignored for the comparison
+fn is_solution(epd: &Epd, san: &str) -> bool {
+    let matches = |moves: &[String]| {
+        moves
+            .iter()
+            .any(|mov| mov.trim_end_matches(['+', '#']) == san.trim_end_matches(['+', '#']))
+    };
+    (epd.best_moves.is_empty() || matches(&epd.best_moves)) && !matches(&epd.avoid_moves)
+}
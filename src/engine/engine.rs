@@ -2,76 +2,253 @@ use log::info;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering;
 use std::sync::mpsc;
+use smallvec::SmallVec;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use std::time::SystemTime;
 
-use crate::board::{board::Chessboard, promotion::Promotion};
+use crate::board::{
+    board::Chessboard,
+    chess_move::{Move, MoveType},
+    square::{Rank, Square},
+};
 use crate::engine::transposition::transposition::Flag;
 use crate::evaluation::evaluate_for_own_color;
-use crate::TRANSPOSITION_TABLE;
+use crate::figures::piece::{Piece, PIECE_VALUES};
+use crate::{LATE_MOVE_REDUCTIONS, TRANSPOSITION_TABLE};
 
-use super::transposition::table::get_entry;
+use super::transposition::table::{get_entry, get_entry_without_check, TranspositionTable};
 use super::{
-    moves::get_valid_moves_in_position, sender::send_move,
+    attacks::get_checkers,
+    history::{start_new_search, with_thread_history, SearchHistory},
+    move_picker::MovePicker,
+    moves::{get_threats, is_legal, Threats},
+    pv::{PvTable, MAX_PLY},
+    see::see_ge,
+    sender::{convert_move_to_uci, send_move},
     transposition::transposition::Transposition,
 };
 
 const PLACEHOLDER_RATING: f32 = 5000.0;
+// smallest difference of two ratings we care about (one centipawn) - width of the null window
+const NULL_WINDOW: f32 = 0.01;
+// every rating from here on is a mate
+const MATE_RATING: f32 = 3000.0;
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+// the null move is searched with 1 + reduction plies less (a bit more in deep searches)
+const NULL_MOVE_REDUCTION: u8 = 2;
+const NULL_MOVE_VERIFICATION_DEPTH: u8 = 8;
+// late move reductions - ln(depth) * ln(move number) / divisor plies (plus the base) for late quiet moves
+const LMR_BASE: f32 = 0.75;
+const LMR_DIVISOR: f32 = 2.25;
+const LMR_MIN_DEPTH: u8 = 3;
+// moves searched with the full depth before we start to reduce
+const LMR_MIN_MOVES: usize = 3;
+// pruning near the leaves (margins in pawns per remaining depth) - only in null window nodes, not in check
+// reverse futility pruning - the static evaluation is so far above beta that the node is cut without a search
+const REVERSE_FUTILITY_MAX_DEPTH: u8 = 6;
+const REVERSE_FUTILITY_MARGIN: f32 = 0.75;
+// futility pruning - quiet moves can't bring the static evaluation back to alpha
+const FUTILITY_MAX_DEPTH: u8 = 3;
+const FUTILITY_MARGIN: f32 = 1.0;
+// razoring - far below alpha only the captures of the quiescence search are tried
+const RAZORING_MAX_DEPTH: u8 = 2;
+const RAZORING_MARGIN: f32 = 2.0;
+// extensions - forcing moves are searched one ply deeper
+// a line can be extended until it is twice as long as the depth of the iteration (MAX_PLY at most)
+// and all threads of an iteration share a budget of extensions - a tree full of checks can't explode
+const EXTENSION_BUDGET_PER_DEPTH: u32 = 4096;
+// singular extension - the transposition move is much better than all others (checked by a search without it)
+const SINGULAR_MIN_DEPTH: u8 = 8;
+// the transposition entry can be a bit less deep than the node
+const SINGULAR_TRANSPOSITION_DEPTH: u8 = 3;
+// in pawns per remaining depth below the rating of the transposition move
+const SINGULAR_MARGIN: f32 = 0.02;
+// first aspiration window around the rating of the previous depth (in pawns) - doubled on every fail
+const ASPIRATION_WINDOW: f32 = 0.25;
+const ASPIRATION_MAX_WINDOW: f32 = 4.0;
+const ASPIRATION_MIN_DEPTH: u8 = 4;
+// margin for the positional part of the evaluation in the delta pruning of the quiescence search
+const DELTA_MARGIN: f32 = 2.0;
+// time to send the move to the gui - never planned for the search
+pub const MOVE_OVERHEAD: u64 = 100;
+// even with a lot of time on the clock a move should not take longer than 15s
+const MAX_TARGET_TIME: u64 = 15000;
 
 #[derive(Debug, Clone, Copy, Default)]
-pub struct PossibleMove {
-    pub from: usize,
-    pub to: usize,
-    pub promoted_to: Option<Promotion>,
+pub struct MoveWithRating {
+    mov: Move,
+    rating: f32,
+}
+
+// values which are the same for every node of a search
+struct SearchContext<'a> {
+    repetition_is_possible: bool,
+    twice_played_moved: &'a [u64],
+    timer: &'a AtomicBool,
+    // killers, history and countermoves of the thread running the search
+    history: &'a SearchHistory,
+    // depth of the iteration - limits the extensions
+    root_depth: u8,
+    table: &'a TranspositionTable,
+    options: SearchOptions,
+    // extensions used in the iteration (by all threads)
+    extensions: &'a AtomicU32,
 }
 
+impl SearchContext<'_> {
+    fn extension_budget(&self) -> u32 {
+        EXTENSION_BUDGET_PER_DEPTH * self.root_depth as u32
+    }
+
+    fn has_extensions_left(&self) -> bool {
+        self.extensions.load(Ordering::Relaxed) < self.extension_budget()
+    }
+
+    // false once the budget of the iteration is used up
+    fn take_extension(&self) -> bool {
+        self.extensions.fetch_add(1, Ordering::Relaxed) < self.extension_budget()
+    }
+}
+
+// values which change from node to node (besides the board, the window and the depth)
 #[derive(Debug, Clone, Copy)]
-pub struct MoveWithRating {
-    from: usize,
-    to: usize,
-    promoted_to: Option<Promotion>,
-    rating: f32,
+struct SearchNode {
+    ply: u8,
+    // move which led to this position (null move after a null move)
+    previous_move: Move,
+    // a not null excluded move is skipped - the node is only searched to check if it is singular
+    excluded_move: Move,
+    use_transposition: bool,
+    allow_null_move: bool,
 }
 
-impl Default for MoveWithRating {
-    fn default() -> MoveWithRating {
-        MoveWithRating {
-            from: 0,
-            to: 0,
-            promoted_to: None,
-            rating: 0.0,
+impl SearchNode {
+    // regular node reached with the move
+    fn new(ply: u8, previous_move: Move) -> SearchNode {
+        SearchNode {
+            ply,
+            previous_move,
+            excluded_move: Move::default(),
+            use_transposition: true,
+            allow_null_move: true,
+        }
+    }
+
+    // the same position searched again without the shortcuts (verification, singular search)
+    fn without_shortcuts(self) -> SearchNode {
+        SearchNode {
+            use_transposition: false,
+            allow_null_move: false,
+            ..self
         }
     }
 }
 
+// best root move of a finished depth with the line we expect to be played
+#[derive(Debug, Clone, Default)]
+struct RootResult {
+    best_move: MoveWithRating,
+    pv: Vec<Move>,
+    depth: u8,
+    // fail high (lowerbound) or fail low (upperbound) of the aspiration window
+    bound: Flag,
+}
+
+// the search stops after the target time - if the root is unstable (fail high, fail low, falling rating)
+// it may continue until the maximum
+#[derive(Debug, Clone, Copy)]
+pub struct TimeForMove {
+    pub target: u64,
+    pub maximum: u64,
+}
+
+impl TimeForMove {
+    pub fn fixed(time: u64) -> TimeForMove {
+        TimeForMove {
+            target: time,
+            maximum: time,
+        }
+    }
+
+    // time for a move from the remaining time on the clock (wtime / btime, movestogo, winc / binc)
+    pub fn from_clock(remaining: u64, moves_to_go: Option<u64>, increment: Option<u64>) -> TimeForMove {
+        let user_time = match moves_to_go {
+            // +2 to add some buffer for overhead
+            Some(moves_to_go) => remaining / (moves_to_go + 2),
+            // just make some guess on total count of moves to manage time
+            None => remaining / 40,
+        } + increment.unwrap_or(0);
+        // never use the whole remaining time - the move still has to reach the gui
+        // (with almost no time left the search still needs some to finish the first depth)
+        let limit = if remaining > 2 * MOVE_OVERHEAD { remaining - MOVE_OVERHEAD } else { remaining / 2 };
+        // max take 15s, so we dont calculate forever
+        let target = user_time.min(MAX_TARGET_TIME).min(limit);
+        // an unstable search can take more time - but never more than a fifth of the remaining time
+        TimeForMove {
+            target,
+            maximum: (target * 3).min(remaining / 5).max(target).min(limit),
+        }
+    }
+
+    // the search stops at the target - or at the maximum while the root is unstable
+    fn is_up(&self, elapsed: Duration, needs_more_time: bool) -> bool {
+        let elapsed = elapsed.as_millis() as u64;
+        elapsed >= self.maximum || (elapsed >= self.target && !needs_more_time)
+    }
+}
+
+// shared by the search and the thread watching the time
+#[derive(Debug, Clone, Default)]
+struct SearchTimer {
+    // the time is up - the running depth is dropped
+    stop: Arc<AtomicBool>,
+    // the root is unstable - the search may continue after the target time
+    needs_more_time: Arc<AtomicBool>,
+}
+
+// optional parts of the search (UCI options) - off by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchOptions {
+    // taking back on the field of the previous capture is searched one ply deeper
+    pub recapture_extension: bool,
+    // pawn moves to the seventh rank are searched one ply deeper
+    pub pawn_push_extension: bool,
+}
+
 pub fn search_for_best_move(
-    time_for_move: u64,
+    time_for_move: TimeForMove,
     board: &Chessboard,
     repetition_is_possible: bool,
     twice_played_moved: &[u64],
-) {
-    let (best_move, depth) = calculate_root_level(
+    options: SearchOptions,
+) -> (Move, f32, u8, Vec<Move>) {
+    let (result, depth) = calculate_root_level(
         time_for_move,
         *board,
         repetition_is_possible,
         twice_played_moved.to_owned(),
+        &TRANSPOSITION_TABLE,
+        options,
     );
     info!(
-        "Calculated Positions to depth {} and took {:?}ms - Net Rating: {}",
-        depth, time_for_move, best_move.rating
+        "Calculated Positions to depth {} with {:?} - Net Rating: {}",
+        depth, time_for_move, result.best_move.rating
     );
-    send_move(best_move.from, best_move.to, best_move.promoted_to);
+    // the answer we expect - the gui can think on it during our opponents time
+    send_move(result.best_move.mov, result.pv.get(1).copied());
+    (result.best_move.mov, result.best_move.rating, depth, result.pv)
 }
 
 fn lost_game(depth_to_end: u8) -> MoveWithRating {
     MoveWithRating {
         // m8 in 2 > m8 in 5
-        rating: -3000.0 - depth_to_end as f32,
+        rating: -MATE_RATING - depth_to_end as f32,
         ..Default::default()
     }
 }
@@ -83,283 +260,440 @@ fn draw() -> MoveWithRating {
     }
 }
 
-fn init_best_move(board: &Chessboard, calculate_all_moves: bool) -> f32 {
-    if !calculate_all_moves {
-        return evaluate_for_own_color(board);
-    }
-    -PLACEHOLDER_RATING
-}
-
 fn calculate_root_level(
-    time_for_move: u64,
+    time_for_move: TimeForMove,
     board: Chessboard,
     repetition_is_possible: bool,
     twice_played_moved: Vec<u64>,
-) -> (MoveWithRating, u8) {
+    table: &'static TranspositionTable,
+    options: SearchOptions,
+) -> (RootResult, u8) {
     let (tx, rx) = mpsc::channel();
     let now = SystemTime::now();
-    let timer = Arc::new(AtomicBool::new(false));
-    let mut depth = 0;
-    let best_move_rating = -PLACEHOLDER_RATING;
-    let mut best_move: MoveWithRating = MoveWithRating {
-        rating: best_move_rating,
+    start_new_search();
+    let timer = SearchTimer::default();
+    let mut result = RootResult {
+        best_move: MoveWithRating {
+            rating: -PLACEHOLDER_RATING,
+            ..Default::default()
+        },
         ..Default::default()
     };
 
     // iterative deepening
-    let timer_clone = Arc::clone(&timer);
+    let search_timer = timer.clone();
     thread::spawn(move || {
-        iterative_deepening(
-            board,
-            repetition_is_possible,
-            twice_played_moved.clone(),
-            tx,
-            timer_clone,
-        )
+        iterative_deepening(board, repetition_is_possible, twice_played_moved.clone(), tx, search_timer, table, options)
     });
-    // stop deepening after given time
+    // stop deepening after given time - an unstable root search may use up to the maximum
     thread::spawn(move || {
-        thread::sleep(Duration::from_millis(time_for_move));
-        timer.store(true, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(time_for_move.target));
+        while !time_for_move.is_up(now.elapsed().unwrap_or_default(), timer.needs_more_time.load(Ordering::Relaxed)) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        timer.stop.store(true, Ordering::SeqCst);
     });
 
     for received in rx {
-        depth += 2;
+        let pv: Vec<String> = received.pv.iter().map(|mov| convert_move_to_uci(*mov)).collect();
+        let bound = match received.bound {
+            Flag::Exact => "",
+            Flag::Lowerbound => " lowerbound",
+            Flag::Upperbound => " upperbound",
+        };
         println!(
-            "info depth {} time {} score cp {}",
-            depth,
+            "info depth {} time {} score cp {}{} pv {}",
+            received.depth,
             now.elapsed().unwrap_or(Duration::new(0, 0)).as_millis(),
-            (received.rating * 100.0).round()
+            (received.best_move.rating * 100.0).round(),
+            bound,
+            pv.join(" ")
         );
-        best_move = received;
+        // after a fail low we only know that all moves are worse than expected - the last best move is kept
+        if received.bound != Flag::Upperbound || result.pv.is_empty() {
+            result = received;
+        }
     }
-    (best_move, depth)
+    let depth = result.depth;
+    (result, depth)
 }
 
 fn iterative_deepening(
     board: Chessboard,
     repetition_is_possible: bool,
     twice_played_moved: Vec<u64>,
-    tx: Sender<MoveWithRating>,
-    timer_clone: Arc<AtomicBool>,
+    tx: Sender<RootResult>,
+    timer: SearchTimer,
+    table: &TranspositionTable,
+    options: SearchOptions,
 ) {
-    for max_depth in 2..=100 {
-        let beta = PLACEHOLDER_RATING;
-        if timer_clone.load(Ordering::Relaxed) {
+    let mut previous_rating: Option<f32> = None;
+    for max_depth in 1..=100 {
+        if timer.stop.load(Ordering::Relaxed) {
             break;
         }
-        let (mut valid_moves, _) = get_valid_moves_in_position(&board, true);
-
-        // on odd numbers (we dont really care about, as they end with our move) calculate odd takes to end on opponent move
-        let max_depth_takes = if max_depth % 2 == 0 { 4 } else { 3 };
-        // calculate prev. best move sequential to get baseline alpha
+        // previous best move is calculated first
+        let transposition_move = get_transposition_move(&board, table);
+        let mut valid_moves: Vec<Move> =
+            MovePicker::new(&board, get_threats(&board), transposition_move, [Move::default(); 2], Move::default(), None).collect();
         let first_move = valid_moves.remove(0);
-        let mut new_board = board;
-        new_board.move_figure(first_move.from, first_move.to, first_move.promoted_to);
-        let first_move_calculation = calculate(
-            &new_board,
-            -PLACEHOLDER_RATING,
-            beta,
-            1,
-            max_depth,
-            max_depth_takes,
-            true,
-            repetition_is_possible,
-            &twice_played_moved,
-            &timer_clone,
-            false
-        );
-        let alpha = -first_move_calculation.rating;
-        let mut moves_with_rating: Vec<MoveWithRating> = valid_moves
-            .par_iter()
-            .map(|single| {
-                let mut new_board = board;
-                new_board.move_figure(single.from, single.to, single.promoted_to);
-                let move_with_rating = calculate(
-                    &new_board,
-                    -beta,
-                    -alpha,
-                    1,
-                    max_depth,
-                    max_depth_takes,
-                    true,
+        // every iteration starts with the full budget
+        let extensions = AtomicU32::new(0);
+
+        let search = |mov: Move, alpha: f32, beta: f32, pv: &mut PvTable| {
+            let mut new_board = board;
+            new_board.move_figure(mov);
+            let rating = with_thread_history(|history| {
+                let context = SearchContext {
                     repetition_is_possible,
-                    &twice_played_moved,
-                    &timer_clone,
-                    false
+                    twice_played_moved: &twice_played_moved,
+                    timer: &timer.stop,
+                    history,
+                    root_depth: max_depth,
+                    table,
+                    options,
+                    extensions: &extensions,
+                };
+                let node = SearchNode {
+                    use_transposition: false,
+                    ..SearchNode::new(1, mov)
+                };
+                -calculate(&new_board, -beta, -alpha, max_depth - 1, node, &context, pv).rating
+            });
+            pv.update(0, mov);
+            RootResult {
+                best_move: MoveWithRating { mov, rating },
+                pv: pv.get_line(0).to_vec(),
+                depth: max_depth,
+                bound: Flag::Exact,
+            }
+        };
+        let search_root = |window_alpha: f32, window_beta: f32| {
+            // calculate prev. best move sequential with the full window to get baseline alpha
+            let first_move_calculation = search(first_move, window_alpha, window_beta, &mut PvTable::default());
+            let alpha = first_move_calculation.best_move.rating.max(window_alpha);
+            // all other moves only have to show they are not better than the first one (null window)
+            // if one of them is, we need its exact rating and line
+            let mut results: Vec<RootResult> = valid_moves
+                .par_iter()
+                .map(|single| {
+                    let mut pv = PvTable::default();
+                    let result = search(*single, alpha, alpha + NULL_WINDOW, &mut pv);
+                    if result.best_move.rating > alpha {
+                        return search(*single, alpha, window_beta, &mut pv);
+                    }
+                    result
+                })
+                .collect();
+
+            // add back best move
+            results.push(first_move_calculation);
+            results
+                .into_iter()
+                .max_by(|a, b| a.best_move.rating.partial_cmp(&b.best_move.rating).unwrap())
+        };
+
+        // aspiration window - we expect the rating of the previous depth, a window around it causes more cutoffs
+        // if the rating is outside, the failing side is widened until the full window is reached
+        let mut delta = ASPIRATION_WINDOW;
+        let (mut window_alpha, mut window_beta) = match previous_rating {
+            Some(rating) if max_depth >= ASPIRATION_MIN_DEPTH && rating.abs() < MATE_RATING => {
+                (rating - delta, rating + delta)
+            }
+            _ => (-PLACEHOLDER_RATING, PLACEHOLDER_RATING),
+        };
+        while let Some(mut depth_best_move) = search_root(window_alpha, window_beta) {
+            // prevent sending not calculated moves
+            if timer.stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let rating = depth_best_move.best_move.rating;
+            delta *= 2.0;
+            if rating <= window_alpha {
+                depth_best_move.bound = Flag::Upperbound;
+                window_alpha = if delta > ASPIRATION_MAX_WINDOW { -PLACEHOLDER_RATING } else { rating - delta };
+            } else if rating >= window_beta {
+                depth_best_move.bound = Flag::Lowerbound;
+                window_beta = if delta > ASPIRATION_MAX_WINDOW { PLACEHOLDER_RATING } else { rating + delta };
+            } else {
+                // a falling rating is a sign of trouble - the next depth may find a way out
+                timer.needs_more_time.store(
+                    previous_rating.is_some_and(|previous| rating < previous - ASPIRATION_WINDOW),
+                    Ordering::Relaxed,
                 );
-                MoveWithRating {
-                    from: single.from,
-                    to: single.to,
-                    promoted_to: single.promoted_to,
-                    rating: -move_with_rating.rating,
-                }
-            })
-            .collect();
-
-        // add back best move
-        moves_with_rating.push(MoveWithRating {
-            from: first_move.from,
-            to: first_move.to,
-            promoted_to: first_move.promoted_to,
-            rating: -first_move_calculation.rating,
-        });
-
-        // prevent sending not calculated moves
-        if timer_clone.load(Ordering::Relaxed) {
-            break;
-        }
-        if max_depth % 2 != 0 {
-            // we only want calculations ending on opponent moves
-            continue;
-        }
-        let depth_best_move_opt = moves_with_rating
-            .iter()
-            .max_by(|a, b| a.rating.partial_cmp(&b.rating).unwrap());
-        if let Some(depth_best_move) = depth_best_move_opt {
-            let _ = tx.send(*depth_best_move);
+                previous_rating = Some(rating);
+                let _ = tx.send(depth_best_move);
+                break;
+            }
+            // the root is unstable - the re-search should not be cut by the target time
+            timer.needs_more_time.store(true, Ordering::Relaxed);
+            let _ = tx.send(depth_best_move);
         }
     }
     drop(tx)
 }
 
+// principal variation search - only the first move of a node is searched with the full window
+// the best line is collected in the pv table (by ply), the quiescence search takes over at the end of the depth
 fn calculate(
     board: &Chessboard,
     mut alpha: f32,
     beta: f32,
-    depth: u8,
-    max_depth: u8,
-    max_depth_takes: u8,
-    calculate_all_moves: bool,
-    repetition_is_possible: bool,
-    twice_played_moved: &Vec<u64>,
-    timer: &AtomicBool,
-    use_transposition: bool,
+    depth_to_end: u8,
+    node: SearchNode,
+    context: &SearchContext,
+    pv: &mut PvTable,
 ) -> MoveWithRating {
+    let SearchNode {
+        ply,
+        previous_move,
+        excluded_move,
+        use_transposition,
+        allow_null_move,
+    } = node;
+    pv.clear(ply as usize);
     // todo: check if timer can be removed from calculation as it is dropped in other position anyways?
-    if timer.load(Ordering::Relaxed) || (depth == max_depth_takes && !calculate_all_moves) {
-        let evaluation = evaluate_for_own_color(board);
-        // init without a best move is no issue as long as we calculate more than depth = 1
+    if context.timer.load(Ordering::Relaxed) {
         return MoveWithRating {
-            rating: evaluation,
+            rating: evaluate_for_own_color(board),
             ..Default::default()
         };
     }
-    // calculate only takes
-    if depth == max_depth && calculate_all_moves {
-        return calculate(
-            board,
-            alpha,
-            beta,
-            0,
-            max_depth,
-            max_depth_takes,
-            false,
-            repetition_is_possible,
-            twice_played_moved,
-            timer,
-            true,
-        );
+    if depth_to_end == 0 {
+        return MoveWithRating {
+            rating: quiescence(board, alpha, beta, 0, context),
+            ..Default::default()
+        };
     }
-    let depth_to_end = if calculate_all_moves {
-        max_depth + max_depth_takes - depth
-    } else {
-        max_depth_takes - depth
-    };
+    // attacks, pins and checks - for the transposition move, the singular move and the move generation
+    let threats = get_threats(board);
     if use_transposition {
-        if let Some(val) = get_entry(board.zobrist_key, depth_to_end, alpha, beta) {
+        if let Some(val) = get_entry(context.table, board.zobrist_key, depth_to_end, alpha, beta) {
+            // an entry of another position with the same key has no legal move here - its value is useless as well
             // only use value from transposition if it does not result in a repetition
-            if !(repetition_is_possible
-                && results_in_repetition(val, &mut board.clone(), twice_played_moved))
+            if is_legal(board, &threats, val.best_move)
+                && !(context.repetition_is_possible
+                    && results_in_repetition(val, &mut board.clone(), context.twice_played_moved))
             {
                 return MoveWithRating {
-                    from: val.best_move.from,
-                    to: val.best_move.to,
-                    promoted_to: val.best_move.promoted_to,
+                    mov: val.best_move,
                     rating: val.evaluation,
                 };
             }
         }
     }
 
-    let mut best_move_rating = init_best_move(board, calculate_all_moves);
-    let (valid_moves, is_in_check) = get_valid_moves_in_position(board, calculate_all_moves);
-    if is_in_check && valid_moves.is_empty() {
-        return lost_game(depth_to_end);
-    } else if calculate_all_moves && valid_moves.is_empty() && !is_in_check {
-        return draw();
-    } else if valid_moves.is_empty() && !is_in_check {
-        // in this case no draw just no takes left to be checked
+    let transposition_move = get_transposition_move(board, context.table);
+    let killers = context.history.get_killers(ply as usize);
+    let countermove = context.history.get_countermove(previous_move);
+    let move_picker = MovePicker::new(board, threats, transposition_move, killers, countermove, Some(context.history));
+    let is_in_check = move_picker.is_in_check();
+    // the pv nodes (full window) are searched without pruning - the width is compared with some room for rounding
+    let can_prune = !is_in_check
+        && excluded_move.is_null()
+        && beta - alpha < 2.0 * NULL_WINDOW
+        && alpha.abs() < MATE_RATING
+        && beta.abs() < MATE_RATING;
+    let static_evaluation = if is_in_check { lost_game(0).rating } else { evaluate_for_own_color(board) };
+
+    if can_prune
+        && depth_to_end <= REVERSE_FUTILITY_MAX_DEPTH
+        && static_evaluation - REVERSE_FUTILITY_MARGIN * depth_to_end as f32 >= beta
+    {
         return MoveWithRating {
-            rating: best_move_rating,
+            rating: static_evaluation,
             ..Default::default()
         };
     }
-    let mut transposition_flag = Flag::Exact;
+    if can_prune
+        && depth_to_end <= RAZORING_MAX_DEPTH
+        && static_evaluation + RAZORING_MARGIN * depth_to_end as f32 <= alpha
+    {
+        let rating = quiescence(board, alpha, beta, 0, context);
+        if rating <= alpha {
+            return MoveWithRating {
+                rating,
+                ..Default::default()
+            };
+        }
+    }
+
+    // null move pruning - if passing the turn is still good enough for a cutoff, one of our moves will be as well
+    // not in check (passing is illegal), not with only pawns left (zugzwang) and never twice in a row
+    if allow_null_move
+        && !is_in_check
+        && depth_to_end >= NULL_MOVE_MIN_DEPTH
+        && beta.abs() < MATE_RATING
+        && has_pieces(board)
+        && static_evaluation >= beta
+    {
+        let reduction = NULL_MOVE_REDUCTION + depth_to_end / 4;
+        let mut null_board = *board;
+        null_board.make_null_move();
+        let null_node = SearchNode {
+            allow_null_move: false,
+            ..SearchNode::new(ply + 1, Move::default())
+        };
+        let rating = -calculate(
+            &null_board,
+            -beta,
+            -beta + NULL_WINDOW,
+            depth_to_end.saturating_sub(reduction + 1),
+            null_node,
+            context,
+            pv,
+        )
+        .rating;
+        if rating >= beta {
+            // deep nodes are verified with a reduced search without null moves (zugzwang with pieces)
+            let is_verified = depth_to_end < NULL_MOVE_VERIFICATION_DEPTH
+                || calculate(board, beta - NULL_WINDOW, beta, depth_to_end - reduction, node.without_shortcuts(), context, pv)
+                    .rating
+                    >= beta;
+            if is_verified {
+                // a mate after passing the turn is no proof
+                return MoveWithRating {
+                    rating: if rating >= MATE_RATING { beta } else { rating },
+                    ..Default::default()
+                };
+            }
+        }
+    }
+
+    let can_extend = ply as usize + (depth_to_end as usize) < (2 * context.root_depth as usize).min(MAX_PLY)
+        && context.has_extensions_left();
+    let is_singular = can_extend
+        && excluded_move.is_null()
+        && depth_to_end >= SINGULAR_MIN_DEPTH
+        && is_singular_move(board, &threats, depth_to_end, node, transposition_move, context, pv);
+
+    let mut best_move_rating = -PLACEHOLDER_RATING;
+    let original_alpha = alpha;
     let mut best_move: MoveWithRating = MoveWithRating {
         rating: best_move_rating,
         ..Default::default()
     };
-    for single in valid_moves.into_iter() {
+    let mut moves_searched: usize = 0;
+    // moves which did not cause a cutoff - they get a malus in their history if a later move does
+    let mut failed_quiets: SmallVec<[Move; 64]> = SmallVec::new();
+    let mut failed_captures: SmallVec<[Move; 32]> = SmallVec::new();
+    let is_futile = can_prune
+        && depth_to_end <= FUTILITY_MAX_DEPTH
+        && static_evaluation + FUTILITY_MARGIN * depth_to_end as f32 <= alpha;
+    for single in move_picker {
         let mut new_board = *board;
-        new_board.move_figure(single.from, single.to, single.promoted_to);
+        new_board.move_figure(single);
+        if single == excluded_move {
+            continue;
+        }
+        let is_quiet = !single.is_capture() && !single.is_promotion();
+        let is_check = gives_check(&new_board);
+        // one move is always searched - otherwise we could not tell a mate or stalemate
+        if is_futile && moves_searched > 0 && is_quiet && !is_check {
+            continue;
+        }
+        let is_singular_move = is_singular && single == transposition_move;
+        let extension = if can_extend
+            && is_forcing(board, single, previous_move, is_check, is_singular_move, context.options)
+            && context.take_extension()
+        {
+            1
+        } else {
+            0
+        };
+        let new_depth = depth_to_end - 1 + extension;
+        let mut search = |alpha: f32, beta: f32, depth_to_end: u8| {
+            -calculate(&new_board, -beta, -alpha, depth_to_end, SearchNode::new(ply + 1, single), context, pv).rating
+        };
 
         // check for repetition
-        let move_with_rating =
-            if repetition_is_possible && twice_played_moved.contains(&new_board.zobrist_key) {
-                MoveWithRating {
-                    rating: 0.0,
-                    ..Default::default()
-                }
+        let adjusted_evaluation = if context.repetition_is_possible
+            && context.twice_played_moved.contains(&new_board.zobrist_key)
+        {
+            pv.clear(ply as usize + 1);
+            0.0
+        } else if moves_searched == 0 {
+            search(alpha, beta, new_depth)
+        } else {
+            // late quiet moves are rarely the best - they only get a reduced search unless they turn out to be good
+            let reduction = if !is_in_check
+                && depth_to_end >= LMR_MIN_DEPTH
+                && moves_searched >= LMR_MIN_MOVES
+                && single != transposition_move
+                && !killers.contains(&single)
+                && single != countermove
+                && is_quiet
+                && !is_check
+            {
+                LATE_MOVE_REDUCTIONS[(depth_to_end as usize).min(63)][moves_searched.min(63)].min(new_depth)
             } else {
-                calculate(
-                    &new_board,
-                    -beta,
-                    -alpha,
-                    depth + 1,
-                    max_depth,
-                    max_depth_takes,
-                    calculate_all_moves,
-                    repetition_is_possible,
-                    twice_played_moved,
-                    timer,
-                    true
-                )
+                0
             };
-        let adjusted_evaluation = -move_with_rating.rating;
+            search_late_move(&mut search, alpha, beta, new_depth, reduction)
+        };
+        moves_searched += 1;
         if best_move_rating < adjusted_evaluation {
             best_move_rating = adjusted_evaluation;
             best_move = MoveWithRating {
-                from: single.from,
-                to: single.to,
-                promoted_to: single.promoted_to,
+                mov: single,
                 rating: adjusted_evaluation,
-            }
+            };
+            pv.update(ply as usize, single);
         }
         alpha = alpha.max(adjusted_evaluation);
         if alpha >= beta {
+            if is_quiet {
+                context.history.update_quiet_cutoff(
+                    board.current_move,
+                    ply as usize,
+                    depth_to_end,
+                    previous_move,
+                    single,
+                    &failed_quiets,
+                );
+                context.history.update_failed_captures(board, depth_to_end, &failed_captures);
+            } else {
+                context.history.update_capture_cutoff(board, depth_to_end, single, &failed_captures);
+            }
             break;
         }
+        if is_quiet {
+            failed_quiets.push(single);
+        } else {
+            failed_captures.push(single);
+        }
     }
-    // dont save best move from only takes in transposition table
-    if calculate_all_moves {
-        if best_move_rating <= alpha {
-            transposition_flag = Flag::Upperbound;
-        } else if best_move_rating >= beta {
-            transposition_flag = Flag::Lowerbound;
+    if moves_searched == 0 {
+        // the excluded move is the only one - it is singular
+        if !excluded_move.is_null() {
+            return MoveWithRating {
+                rating: alpha,
+                ..Default::default()
+            };
         }
+        if is_in_check {
+            return lost_game(depth_to_end);
+        }
+        return draw();
+    }
+    // compared with the window we were called with - alpha was raised by the moves
+    let transposition_flag = if best_move_rating <= original_alpha {
+        Flag::Upperbound
+    } else if best_move_rating >= beta {
+        Flag::Lowerbound
+    } else {
+        Flag::Exact
+    };
 
-        TRANSPOSITION_TABLE.insert(
+    // without the excluded move the result says nothing about the position
+    if excluded_move.is_null() {
+        context.table.insert(
             board.zobrist_key,
             Transposition {
                 hash: board.zobrist_key,
                 depth: depth_to_end,
                 evaluation: best_move_rating,
-                best_move: PossibleMove {
-                    from: best_move.from,
-                    to: best_move.to,
-                    promoted_to: best_move.promoted_to,
-                },
+                best_move: best_move.mov,
                 flag: transposition_flag,
             },
         );
@@ -367,17 +701,194 @@ fn calculate(
     best_move
 }
 
-// if repetition is possible make move and check if it is a repetition
+// captures are searched until the position is quiet - we never evaluate in the middle of an exchange
+// the static evaluation is a lower bound (stand pat), as we don't have to capture - unless we are in check
+// ply counts from the max depth of the main search, only the first one looks at silent checks
+fn quiescence(board: &Chessboard, mut alpha: f32, beta: f32, ply: u8, context: &SearchContext) -> f32 {
+    if context.timer.load(Ordering::Relaxed) {
+        return evaluate_for_own_color(board);
+    }
+    let threats = get_threats(board);
+    // every entry of the main search is deep enough - the quiescence search does not store its results
+    let transposition_move = match get_entry(context.table, board.zobrist_key, 0, alpha, beta) {
+        Some(val) if is_legal(board, &threats, val.best_move) => return val.evaluation,
+        _ => get_transposition_move(board, context.table),
+    };
+    // captures are ordered by MVV-LVA and capture history, captures losing material (SEE) are not returned
+    // all evasions if we are in check
+    let move_picker = if ply == 0 {
+        // checks at the horizon can lead to a mate the captures alone don't see
+        MovePicker::new_captures_and_checks(board, threats, transposition_move, Some(context.history))
+    } else {
+        MovePicker::new_captures(board, threats, transposition_move, Some(context.history))
+    };
+    let is_in_check = move_picker.is_in_check();
+    let stand_pat = if is_in_check {
+        lost_game(0).rating
+    } else {
+        evaluate_for_own_color(board)
+    };
+    if stand_pat >= beta {
+        return stand_pat;
+    }
+    alpha = alpha.max(stand_pat);
+    let mut best_rating = stand_pat;
+    for single in move_picker {
+        // delta pruning - even getting the piece for free does not bring us back to alpha
+        if !is_in_check
+            && single.is_capture()
+            && !single.is_promotion()
+            && stand_pat + get_captured_value(board, single) + DELTA_MARGIN <= alpha
+        {
+            continue;
+        }
+        let mut new_board = *board;
+        new_board.move_figure(single);
+        let rating = -quiescence(&new_board, -beta, -alpha, ply + 1, context);
+        if rating > best_rating {
+            best_rating = rating;
+            alpha = alpha.max(rating);
+            if alpha >= beta {
+                break;
+            }
+        }
+    }
+    best_rating
+}
+
+// by remaining depth and number of the move (both capped at 63)
+// the first move is expected to be the best - prove the others are worse with a null window
+// a reduced move beating alpha is searched again with the full depth, a move inside the window with the full window
+fn search_late_move(
+    mut search: impl FnMut(f32, f32, u8) -> f32,
+    alpha: f32,
+    beta: f32,
+    depth_to_end: u8,
+    reduction: u8,
+) -> f32 {
+    let mut rating = search(alpha, alpha + NULL_WINDOW, depth_to_end - reduction);
+    if reduction > 0 && rating > alpha {
+        rating = search(alpha, alpha + NULL_WINDOW, depth_to_end);
+    }
+    if rating > alpha && rating < beta {
+        search(alpha, beta, depth_to_end)
+    } else {
+        rating
+    }
+}
+
+pub fn get_late_move_reductions() -> [[u8; 64]; 64] {
+    let mut reductions = [[0; 64]; 64];
+    for (depth, row) in reductions.iter_mut().enumerate().skip(1) {
+        for (move_number, reduction) in row.iter_mut().enumerate().skip(1) {
+            *reduction = (LMR_BASE + (depth as f32).ln() * (move_number as f32).ln() / LMR_DIVISOR) as u8;
+        }
+    }
+    reductions
+}
+
+// the opponent (side to move after our move) is in check
+// the transposition move is singular if all other moves fail low against a beta a bit below its rating
+// searched with half the depth - the entry needs to be about as deep as the node and at least a lower bound
+fn is_singular_move(
+    board: &Chessboard,
+    threats: &Threats,
+    depth_to_end: u8,
+    node: SearchNode,
+    transposition_move: Move,
+    context: &SearchContext,
+    pv: &mut PvTable,
+) -> bool {
+    let Some(entry) = get_entry_without_check(context.table, board.zobrist_key) else {
+        return false;
+    };
+    if entry.best_move != transposition_move
+        || entry.flag == Flag::Upperbound
+        || entry.depth < depth_to_end.saturating_sub(SINGULAR_TRANSPOSITION_DEPTH)
+        || entry.evaluation.abs() >= MATE_RATING
+        || !is_legal(board, threats, transposition_move)
+    {
+        return false;
+    }
+    let singular_beta = entry.evaluation - SINGULAR_MARGIN * depth_to_end as f32;
+    let singular_node = SearchNode {
+        excluded_move: transposition_move,
+        ..node.without_shortcuts()
+    };
+    let rating = calculate(
+        board,
+        singular_beta - NULL_WINDOW,
+        singular_beta,
+        (depth_to_end - 1) / 2,
+        singular_node,
+        context,
+        pv,
+    )
+    .rating;
+    // the line of the search without the transposition move is not ours
+    pv.clear(node.ply as usize);
+    rating < singular_beta
+}
+
+// moves which are searched one ply deeper - checks giving away material are no forcing line
+fn is_forcing(
+    board: &Chessboard,
+    mov: Move,
+    previous_move: Move,
+    is_check: bool,
+    is_singular_move: bool,
+    options: SearchOptions,
+) -> bool {
+    (is_check && see_ge(board, mov, 0))
+        || is_singular_move
+        || (options.recapture_extension && is_recapture(previous_move, mov))
+        || (options.pawn_push_extension && is_pawn_push_to_seventh_rank(board, mov))
+}
+
+// taking back on the field of the previous capture
+fn is_recapture(previous_move: Move, mov: Move) -> bool {
+    previous_move.is_capture() && mov.is_capture() && mov.to() == previous_move.to()
+}
+
+// a pawn on the seventh rank is always passed - the promotion is one move away
+fn is_pawn_push_to_seventh_rank(board: &Chessboard, mov: Move) -> bool {
+    board.get_pieces(board.current_move, Piece::Pawn).field_is_used(mov.from())
+        && Square::from_index(mov.to()).rank().relative_to(board.current_move) == Rank::SEVENTH
+}
+
+fn gives_check(board: &Chessboard) -> bool {
+    let king_position = board.get_pieces(board.current_move, Piece::King).get_first_field();
+    !get_checkers(board, king_position).is_empty()
+}
+
+// knights, bishops, rooks or queens of the side to move - with only pawns zugzwang is common
+fn has_pieces(board: &Chessboard) -> bool {
+    [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+        .into_iter()
+        .any(|piece| !board.get_pieces(board.current_move, piece).is_empty())
+}
+
+// value of the captured piece in the units of the evaluation (pawns)
+fn get_captured_value(board: &Chessboard, mov: Move) -> f32 {
+    let captured = match mov.move_type() {
+        MoveType::EnPassant => Some(Piece::Pawn),
+        _ => board.get_type_of_figure(board.get_opponent_color(), mov.to()),
+    };
+    captured.map_or(0, |piece| PIECE_VALUES[piece as usize]) as f32 / 100.0
+}
+
+// best move of a previous search - could be from another position with the same key
+fn get_transposition_move(board: &Chessboard, table: &TranspositionTable) -> Move {
+    get_entry_without_check(table, board.zobrist_key).map_or(Move::default(), |entry| entry.best_move)
+}
+
+// if repetition is possible make move and check if it is a repetition - the move has to be legal
 fn results_in_repetition(
     transposition: Transposition,
     board: &mut Chessboard,
-    twice_played_moved: &Vec<u64>,
+    twice_played_moved: &[u64],
 ) -> bool {
-    board.move_figure(
-        transposition.best_move.from,
-        transposition.best_move.to,
-        transposition.best_move.promoted_to,
-    );
+    board.move_figure(transposition.best_move);
     twice_played_moved.contains(&board.zobrist_key)
 }
 
@@ -385,64 +896,469 @@ fn results_in_repetition(
 
 #[cfg(test)]
 mod tests {
+    use rustc_hash::FxHashMap;
+
+    use crate::engine::moves::get_valid_moves_in_position;
+
     use super::*;
 
+    fn create_context<'a>(
+        repetition_is_possible: bool,
+        twice_played_moved: &'a [u64],
+        timer: &'a AtomicBool,
+        history: &'a SearchHistory,
+        table: &'a TranspositionTable,
+        extensions: &'a AtomicU32,
+    ) -> SearchContext<'a> {
+        // deep enough - the extensions are not limited in the tests
+        SearchContext {
+            repetition_is_possible,
+            twice_played_moved,
+            timer,
+            history,
+            root_depth: 64,
+            table,
+            options: SearchOptions::default(),
+            extensions,
+        }
+    }
+
+    // every test searches with its own table - tests run in parallel and some of them fill it with fake entries
+    // the search threads of the root need a static one, so it is leaked
+    fn create_table() -> &'static TranspositionTable {
+        Box::leak(Box::default())
+    }
+
+    // simulates a table with a 4 bit key space - positions with the same lowest bits of the key
+    // get the best move of each other (or no move at all) as exact entry which is always used
+    fn fill_table_with_collisions(board: &Chessboard, table: &TranspositionTable) -> Vec<Chessboard> {
+        let mut boards = vec![*board];
+        let mut last_ply = vec![*board];
+        for _ in 0..2 {
+            last_ply = last_ply
+                .iter()
+                .flat_map(|board| {
+                    let (moves, _) = get_valid_moves_in_position(board, true);
+                    moves.into_iter().map(|mov| {
+                        let mut new_board = *board;
+                        new_board.move_figure(mov);
+                        new_board
+                    })
+                })
+                .collect();
+            boards.extend(&last_ply);
+        }
+        let mut buckets: FxHashMap<u64, Vec<Chessboard>> = FxHashMap::default();
+        for board in &boards {
+            buckets.entry(board.zobrist_key & 0b1111).or_default().push(*board);
+        }
+        for bucket in buckets.values() {
+            for (index, board) in bucket.iter().enumerate() {
+                let colliding = bucket[(index + 1) % bucket.len()];
+                let (moves, _) = get_valid_moves_in_position(&colliding, true);
+                table.insert(
+                    board.zobrist_key,
+                    Transposition {
+                        hash: board.zobrist_key,
+                        depth: u8::MAX,
+                        evaluation: 0.0,
+                        best_move: moves.first().copied().unwrap_or_default(),
+                        flag: Flag::Exact,
+                    },
+                );
+            }
+        }
+        boards
+    }
+
     #[test]
-    fn test_sacking_queen() {
-        // sacked queen by moving knight
-        let position =
-            String::from("r3k2r/pppq1pp1/2n1p2p/3p1b2/1b1PnP2/2N1P1B1/PPPQB1PP/R4KNR w kq - 8 11");
+    fn test_colliding_transpositions_are_not_played() {
+        let board = Chessboard::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+        let table = create_table();
+        let boards = fill_table_with_collisions(&board, table);
 
-        let mut board = Chessboard {
-            ..Default::default()
+        let timer = AtomicBool::new(false);
+        for board in &boards {
+            let result = calculate(
+                board,
+                -PLACEHOLDER_RATING,
+                PLACEHOLDER_RATING,
+                1,
+                SearchNode::new(1, Move::default()),
+                &create_context(true, &Vec::new(), &timer, &SearchHistory::default(), table, &AtomicU32::new(0)),
+                &mut PvTable::default(),
+            );
+            assert!(
+                result.mov.is_null() || is_legal(board, &get_threats(board), result.mov),
+                "{:?} is not legal in {}", result.mov, board.zobrist_key
+            );
+        }
+
+        let (result, _) = calculate_root_level(TimeForMove::fixed(300), board, true, Vec::new(), table, SearchOptions::default());
+        let (moves, _) = get_valid_moves_in_position(&board, true);
+        assert!(moves.contains(&result.best_move.mov));
+    }
+
+    #[test]
+    fn test_time_for_move() {
+        let time_for_move = TimeForMove {
+            target: 300,
+            maximum: 1500,
+        };
+        let is_up = |elapsed, needs_more_time| time_for_move.is_up(Duration::from_millis(elapsed), needs_more_time);
+        assert!(!is_up(299, false));
+        assert!(is_up(300, false));
+        // an unstable root may continue until the maximum
+        assert!(!is_up(300, true));
+        assert!(!is_up(1499, true));
+        assert!(is_up(1500, true));
+        assert!(!TimeForMove::fixed(300).is_up(Duration::from_millis(299), true));
+        assert!(TimeForMove::fixed(300).is_up(Duration::from_millis(300), true));
+    }
+
+    #[test]
+    fn test_time_from_clock() {
+        let time = |remaining, moves_to_go, increment| {
+            let time_for_move = TimeForMove::from_clock(remaining, moves_to_go, increment);
+            (time_for_move.target, time_for_move.maximum)
         };
-        board.create_position_from_input_string(position);
+        // 40 moves to go by default, three times the target for an unstable root
+        assert_eq!(time(60000, None, None), (1500, 4500));
+        assert_eq!(time(60000, Some(18), Some(1000)), (4000, 12000));
+        // never more than a fifth of the remaining time
+        assert_eq!(time(60000, Some(8), None), (6000, 12000));
+        assert_eq!(time(600000, Some(0), None), (15000, 45000));
+        // the increment may exceed the remaining time - both are clamped to the remaining time minus the overhead
+        assert_eq!(time(1000, None, Some(2000)), (900, 900));
+        // almost no time left - half of it is used
+        assert_eq!(time(150, None, Some(2000)), (75, 75));
+    }
 
-        let (best_move, _) = calculate_root_level(5000, board, false, Vec::new());
-        assert_ne!(best_move.from, 18);
+    #[test]
+    fn test_sacking_queen() {
+        // sacked queen by moving knight
+        let board = Chessboard::from_fen("r3k2r/pppq1pp1/2n1p2p/3p1b2/1b1PnP2/2N1P1B1/PPPQB1PP/R4KNR w kq - 8 11");
+
+        let (result, _) = calculate_root_level(TimeForMove::fixed(5000), board, false, Vec::new(), create_table(), SearchOptions::default());
+        assert_ne!(result.best_move.mov.from(), 18);
     }
 
     #[test]
     fn test_not_taking_figure() {
         // does not take +3 figure
-        let position =
-            String::from("2r1kb1r/pppq1ppp/4p3/3pPb2/4NB2/4P3/PPPQBPPP/R3K2R b KQk - 0 11");
-
-        let mut board = Chessboard {
-            ..Default::default()
-        };
-        board.create_position_from_input_string(position);
+        let board = Chessboard::from_fen("2r1kb1r/pppq1ppp/4p3/3pPb2/4NB2/4P3/PPPQBPPP/R3K2R b KQk - 0 11");
 
-        let (best_move, _) = calculate_root_level(5000, board, false, Vec::new());
-        assert_eq!(best_move.to, 28);
+        let (result, _) = calculate_root_level(TimeForMove::fixed(5000), board, false, Vec::new(), create_table(), SearchOptions::default());
+        assert_eq!(result.best_move.mov.to(), 28);
     }
 
     #[test]
     fn test_sacking_rook() {
         // was sacking rook at d4
-        let position = String::from("8/5ppp/2ppk3/P2p4/3Pr1b1/4B1R1/1r5P/2R3K1 b - - 5 45");
+        let board = Chessboard::from_fen("8/5ppp/2ppk3/P2p4/3Pr1b1/4B1R1/1r5P/2R3K1 b - - 5 45");
 
-        let mut board = Chessboard {
-            ..Default::default()
-        };
-        board.create_position_from_input_string(position);
-
-        let (best_move, _) = calculate_root_level(5000, board, false, Vec::new());
-        assert_ne!(best_move.to, 27);
+        let (result, _) = calculate_root_level(TimeForMove::fixed(5000), board, false, Vec::new(), create_table(), SearchOptions::default());
+        assert_ne!(result.best_move.mov.to(), 27);
     }
 
     #[test]
     fn test_sacking_knight() {
         // was sacking knight on a2
-        let position =
-            String::from("r2qkb1r/pppbpp1p/5np1/1B1p4/1n1P1B2/2N1P3/PPP1QPPP/R3K1NR b KQkq - 3 7");
+        let board = Chessboard::from_fen("r2qkb1r/pppbpp1p/5np1/1B1p4/1n1P1B2/2N1P3/PPP1QPPP/R3K1NR b KQkq - 3 7");
+
+        let (result, _) = calculate_root_level(TimeForMove::fixed(5000), board, false, Vec::new(), create_table(), SearchOptions::default());
+        assert_ne!(result.best_move.mov.to(), 8);
+    }
+
+    #[test]
+    fn test_pv_is_legal_line_from_root() {
+        for fen in [
+            "r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 0 1",
+        ] {
+            let mut board = Chessboard::from_fen(fen);
+            let (result, depth) = calculate_root_level(TimeForMove::fixed(1000), board, false, Vec::new(), create_table(), SearchOptions::default());
+            assert!(depth >= 2);
+            // at least our move and the answer of the opponent
+            assert!(result.pv.len() >= 2, "{}: {:?}", fen, result.pv);
+            assert_eq!(result.best_move.mov, result.pv[0]);
+            for mov in &result.pv {
+                assert!(is_legal(&board, &get_threats(&board), *mov), "{}: {:?} in {:?}", fen, mov, result.pv);
+                board.move_figure(*mov);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pv_ends_with_mate() {
+        // mate in two - Rd8+ Rxd8 Rxd8#
+        let mut board = Chessboard::from_fen("r5k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1");
+        let (result, _) = calculate_root_level(TimeForMove::fixed(1000), board, false, Vec::new(), create_table(), SearchOptions::default());
+        assert_eq!(3, result.pv.len(), "{:?}", result.pv);
+        for mov in &result.pv {
+            board.move_figure(*mov);
+        }
+        let (moves, is_in_check) = get_valid_moves_in_position(&board, true);
+        assert!(is_in_check && moves.is_empty(), "{:?}", result.pv);
+    }
+
+    #[test]
+    fn test_quiescence_evasions() {
+        let timer = AtomicBool::new(false);
+        let history = SearchHistory::default();
+        let twice_played_moved = Vec::new();
+        let table = TranspositionTable::default();
+        let extensions = AtomicU32::new(0);
+        let context = create_context(false, &twice_played_moved, &timer, &history, &table, &extensions);
+        // in check without a capture to escape - the king can still move away
+        let board = Chessboard::from_fen("4k3/8/8/8/8/8/8/r3K3 w - - 0 1");
+        assert!(quiescence(&board, -PLACEHOLDER_RATING, PLACEHOLDER_RATING, 1, &context) > -1000.0);
+        // no way out
+        let board = Chessboard::from_fen("4k3/8/8/8/8/8/3q4/3qK3 w - - 0 1");
+        assert_eq!(lost_game(0).rating, quiescence(&board, -PLACEHOLDER_RATING, PLACEHOLDER_RATING, 1, &context));
+    }
+
+    #[test]
+    fn test_quiescence_stand_pat_and_delta_pruning() {
+        let timer = AtomicBool::new(false);
+        let history = SearchHistory::default();
+        let twice_played_moved = Vec::new();
+        let table = TranspositionTable::default();
+        let extensions = AtomicU32::new(0);
+        let context = create_context(false, &twice_played_moved, &timer, &history, &table, &extensions);
+        // Qxd7 loses the queen - we rather stay with the static evaluation
+        let board = Chessboard::from_fen("4k3/3p4/8/8/8/8/8/3QK3 w - - 0 1");
+        let stand_pat = evaluate_for_own_color(&board);
+        assert_eq!(stand_pat, quiescence(&board, -PLACEHOLDER_RATING, PLACEHOLDER_RATING, 1, &context));
+
+        // the free pawn is taken - unless we need a lot more than a pawn
+        let board = Chessboard::from_fen("q3k3/8/8/8/3p4/8/8/3RK3 w - - 0 1");
+        let stand_pat = evaluate_for_own_color(&board);
+        assert!(quiescence(&board, stand_pat - 1.0, stand_pat + 1.0, 1, &context) > stand_pat);
+        assert_eq!(stand_pat, quiescence(&board, stand_pat + 5.0, stand_pat + 6.0, 1, &context));
+    }
+
+    // true if the node was cut by the null move - it is the only way to fail high without a best move
+    fn is_null_move_cutoff(fen: &str) -> bool {
+        let board = Chessboard::from_fen(fen);
+        let beta = evaluate_for_own_color(&board) - 1.0;
+        let node = SearchNode {
+            use_transposition: false,
+            ..SearchNode::new(1, Move::default())
+        };
+        let result = calculate(
+            &board,
+            beta - NULL_WINDOW,
+            beta,
+            4,
+            node,
+            &create_context(
+                false,
+                &Vec::new(),
+                &AtomicBool::new(false),
+                &SearchHistory::default(),
+                &TranspositionTable::default(),
+                &AtomicU32::new(0),
+            ),
+            &mut PvTable::default(),
+        );
+        assert!(result.rating >= beta);
+        result.mov.is_null()
+    }
+
+    #[test]
+    fn test_null_move_pruning() {
+        // a queen up - passing the turn is still good enough
+        assert!(is_null_move_cutoff("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1"));
+        // only pawns left (zugzwang) and in check - we search our moves
+        assert!(!is_null_move_cutoff("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1"));
+        assert!(!is_null_move_cutoff("4k3/8/8/8/8/8/4r3/Q3K3 w - - 0 1"));
+    }
+
+    // searched with a null window around the given offset from the static evaluation - true if no move was searched
+    fn is_pruned(fen: &str, offset: f32, depth_to_end: u8) -> bool {
+        let board = Chessboard::from_fen(fen);
+        let alpha = evaluate_for_own_color(&board) + offset;
+        is_pruned_in_window(&board, alpha, alpha + NULL_WINDOW, depth_to_end)
+    }
+
+    fn is_pruned_in_window(board: &Chessboard, alpha: f32, beta: f32, depth_to_end: u8) -> bool {
+        let history = SearchHistory::default();
+        let twice_played_moved = Vec::new();
+        let timer = AtomicBool::new(false);
+        let table = TranspositionTable::default();
+        let extensions = AtomicU32::new(0);
+        let context = create_context(false, &twice_played_moved, &timer, &history, &table, &extensions);
+        let result = calculate(
+            board,
+            alpha,
+            beta,
+            depth_to_end,
+            SearchNode::new(1, Move::default()).without_shortcuts(),
+            &context,
+            &mut PvTable::default(),
+        );
+        result.mov.is_null()
+    }
+
+    #[test]
+    fn test_reverse_futility_pruning_and_razoring() {
+        // far above beta - cut without a search (null moves are not allowed here)
+        assert!(is_pruned("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1", -5.0, 2));
+        assert!(!is_pruned("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1", -1.0, 2));
+        // far below alpha - the quiescence search does not find anything either
+        assert!(is_pruned("q3k3/8/8/8/8/8/8/4K3 w - - 0 1", 5.0, 2));
+        // in check we always search
+        assert!(!is_pruned("4k3/8/8/8/8/8/4r3/Q3K3 w - - 0 1", -5.0, 2));
+        assert!(!is_pruned("4k3/8/8/8/8/8/4q3/4K3 w - - 0 1", 5.0, 2));
+    }
+
+    #[test]
+    fn test_no_pruning_in_pv_nodes_and_with_mate_scores() {
+        let winning = Chessboard::from_fen("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1");
+        let losing = Chessboard::from_fen("q3k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        let winning_evaluation = evaluate_for_own_color(&winning);
+        let losing_evaluation = evaluate_for_own_color(&losing);
+        // the same margins as above - with a full window the moves are searched
+        assert!(!is_pruned_in_window(&winning, winning_evaluation - 10.0, winning_evaluation - 5.0, 2));
+        assert!(!is_pruned_in_window(&losing, losing_evaluation + 5.0, losing_evaluation + 10.0, 2));
+        // a window at a mate score - the static evaluation says nothing about the mate
+        let mated = lost_game(5).rating;
+        assert!(!is_pruned_in_window(&winning, mated, mated + NULL_WINDOW, 2));
+        assert!(!is_pruned_in_window(&losing, -mated - NULL_WINDOW, -mated, 2));
+    }
+
+    #[test]
+    fn test_excluded_move() {
+        let history = SearchHistory::default();
+        let twice_played_moved = Vec::new();
+        let timer = AtomicBool::new(false);
+        let table = TranspositionTable::default();
+        let extensions = AtomicU32::new(0);
+        let context = create_context(false, &twice_played_moved, &timer, &history, &table, &extensions);
+        // Kxg2 is the only legal move - without it the node fails low (the move is singular)
+        let board = Chessboard::from_fen("7k/8/8/8/8/8/6r1/7K w - - 0 1");
+        let only_move = board.get_move_from_uci_input("h1g2").unwrap();
+        let search = |excluded_move: Move| {
+            let node = SearchNode {
+                excluded_move,
+                ..SearchNode::new(1, Move::default()).without_shortcuts()
+            };
+            calculate(&board, -1.0, 1.0, 3, node, &context, &mut PvTable::default())
+        };
+        let result = search(only_move);
+        assert_eq!(-1.0, result.rating);
+        assert!(result.mov.is_null());
+        // nothing is stored for the incomplete search
+        assert!(get_entry_without_check(&table, board.zobrist_key).is_none());
+        assert_eq!(only_move, search(Move::default()).mov);
+    }
+
+    #[test]
+    fn test_extensions() {
+        let board = Chessboard::from_fen("4k3/8/1P6/8/4P3/8/8/4K3 w - - 0 1");
+        assert!(is_pawn_push_to_seventh_rank(&board, board.get_move_from_uci_input("b6b7").unwrap()));
+        assert!(!is_pawn_push_to_seventh_rank(&board, board.get_move_from_uci_input("e4e5").unwrap()));
+        let board = Chessboard::from_fen("4k3/8/8/8/8/2p5/8/4K3 b - - 0 1");
+        assert!(is_pawn_push_to_seventh_rank(&board, board.get_move_from_uci_input("c3c2").unwrap()));
 
-        let mut board = Chessboard {
+        let board = Chessboard::from_fen("4k3/8/8/3p4/4P3/5N2/8/4K3 b - - 0 1");
+        let pawn_takes = board.get_move_from_uci_input("d5e4").unwrap();
+        let mut new_board = board;
+        new_board.move_figure(pawn_takes);
+        assert!(is_recapture(pawn_takes, new_board.get_move_from_uci_input("f3e4").unwrap()));
+        assert!(!is_recapture(pawn_takes, new_board.get_move_from_uci_input("f3d4").unwrap()));
+        assert!(!is_recapture(Move::default(), new_board.get_move_from_uci_input("f3e4").unwrap()));
+    }
+
+    #[test]
+    fn test_optional_extensions() {
+        let recapture_only = SearchOptions {
+            recapture_extension: true,
             ..Default::default()
         };
-        board.create_position_from_input_string(position);
+        let pawn_push_only = SearchOptions {
+            pawn_push_extension: true,
+            ..Default::default()
+        };
+
+        let board = Chessboard::from_fen("4k3/8/8/3p4/4P3/5N2/8/4K3 b - - 0 1");
+        let pawn_takes = board.get_move_from_uci_input("d5e4").unwrap();
+        let mut new_board = board;
+        new_board.move_figure(pawn_takes);
+        let recapture = new_board.get_move_from_uci_input("f3e4").unwrap();
+        assert!(!is_forcing(&new_board, recapture, pawn_takes, false, false, SearchOptions::default()));
+        assert!(is_forcing(&new_board, recapture, pawn_takes, false, false, recapture_only));
+        assert!(!is_forcing(&new_board, recapture, pawn_takes, false, false, pawn_push_only));
+
+        let board = Chessboard::from_fen("4k3/8/1P6/8/4P3/8/8/4K3 w - - 0 1");
+        let pawn_push = board.get_move_from_uci_input("b6b7").unwrap();
+        assert!(!is_forcing(&board, pawn_push, Move::default(), false, false, SearchOptions::default()));
+        assert!(is_forcing(&board, pawn_push, Move::default(), false, false, pawn_push_only));
+        assert!(!is_forcing(&board, pawn_push, Move::default(), false, false, recapture_only));
+
+        // checks and singular moves don't depend on the options
+        assert!(is_forcing(&board, pawn_push, Move::default(), false, true, SearchOptions::default()));
+    }
+
+    #[test]
+    fn test_extension_budget() {
+        let history = SearchHistory::default();
+        let twice_played_moved = Vec::new();
+        let timer = AtomicBool::new(false);
+        let table = TranspositionTable::default();
+        let extensions = AtomicU32::new(0);
+        let mut context = create_context(false, &twice_played_moved, &timer, &history, &table, &extensions);
+        context.root_depth = 1;
+        assert!(context.has_extensions_left());
+        extensions.store(EXTENSION_BUDGET_PER_DEPTH - 1, Ordering::Relaxed);
+        assert!(context.take_extension());
+        assert!(!context.has_extensions_left());
+        assert!(!context.take_extension());
+        // a deeper iteration has a bigger budget
+        context.root_depth = 2;
+        assert!(context.has_extensions_left());
+    }
 
-        let (best_move, _) = calculate_root_level(5000, board, false, Vec::new());
-        assert_ne!(best_move.to, 8);
+    #[test]
+    fn test_late_move_research() {
+        // the rating of a move at each depth - every search is recorded with its window
+        let late_move = |ratings: [f32; 6], alpha: f32, beta: f32| {
+            let mut searches = Vec::new();
+            let rating = search_late_move(
+                |alpha, beta, depth_to_end| {
+                    searches.push((depth_to_end, beta - alpha > NULL_WINDOW * 2.0));
+                    ratings[depth_to_end as usize]
+                },
+                alpha,
+                beta,
+                5,
+                2,
+            );
+            (rating, searches)
+        };
+        // fails low with the reduced depth - no re-search
+        assert_eq!((0.0, vec![(3, false)]), late_move([0.0; 6], 0.5, 1.0));
+        // fails high with the reduced depth but not with the full depth
+        assert_eq!((0.0, vec![(3, false), (5, false)]), late_move([0.0, 0.0, 0.0, 2.0, 0.0, 0.0], 0.5, 1.0));
+        // fails high with the full depth as well - a cutoff (beta) or an exact rating (pv node)
+        assert_eq!((2.0, vec![(3, false), (5, false)]), late_move([2.0; 6], 0.5, 1.0));
+        assert_eq!((2.0, vec![(3, false), (5, false), (5, true)]), late_move([2.0; 6], 0.5, 3.0));
+    }
+
+    #[test]
+    fn test_late_move_reductions() {
+        let reductions = get_late_move_reductions();
+        assert_eq!(0, reductions[1][1]);
+        assert_eq!(1, reductions[3][LMR_MIN_MOVES]);
+        // more depth left and later moves are reduced more
+        for depth in 1..64 {
+            for move_number in 1..64 {
+                assert!(reductions[depth][move_number] >= reductions[depth - 1][move_number]);
+                assert!(reductions[depth][move_number] >= reductions[depth][move_number - 1]);
+            }
+        }
+        assert!(reductions[20][30] > reductions[3][LMR_MIN_MOVES]);
     }
 }
@@ -0,0 +1,229 @@
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use crate::{
+    board::{
+        board::Chessboard,
+        chess_move::{Move, MoveType},
+    },
+    figures::{color::Color, piece::Piece},
+};
+
+use super::pv::MAX_PLY;
+
+// history scores stay between -MAX_HISTORY and MAX_HISTORY (every update moves them a bit towards the bound)
+const MAX_HISTORY: i32 = 16384;
+
+// increased for every search - the tables of a thread are aged when it sees a new search
+static SEARCH_GENERATION: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+    static SEARCH_HISTORY: SearchHistory = SearchHistory::default();
+}
+
+// move ordering statistics of one search thread, filled by the moves causing a beta cutoff
+// cells - the move picker reads them while the nodes below it update them
+pub struct SearchHistory {
+    generation: Cell<u32>,
+    // two quiet moves by ply which caused a cutoff in a sibling node
+    killers: [[Cell<Move>; 2]; MAX_PLY],
+    // butterfly table - by color, from and to
+    scores: [[[Cell<i32>; 64]; 64]; 2],
+    // quiet reply which refuted the previous move - by from and to of the previous move
+    countermoves: [[Cell<Move>; 64]; 64],
+    // capture history - by moving piece, target field and captured piece
+    captures: [[[Cell<i32>; 6]; 64]; 6],
+}
+
+impl Default for SearchHistory {
+    fn default() -> SearchHistory {
+        SearchHistory {
+            generation: Cell::new(0),
+            killers: std::array::from_fn(|_| std::array::from_fn(|_| Cell::new(Move::default()))),
+            scores: std::array::from_fn(|_| std::array::from_fn(|_| std::array::from_fn(|_| Cell::new(0)))),
+            countermoves: std::array::from_fn(|_| std::array::from_fn(|_| Cell::new(Move::default()))),
+            captures: std::array::from_fn(|_| std::array::from_fn(|_| std::array::from_fn(|_| Cell::new(0)))),
+        }
+    }
+}
+
+impl SearchHistory {
+    pub fn get_killers(&self, ply: usize) -> [Move; 2] {
+        match self.killers.get(ply) {
+            Some(killers) => [killers[0].get(), killers[1].get()],
+            None => [Move::default(); 2],
+        }
+    }
+
+    pub fn get_score(&self, color: Color, mov: Move) -> i32 {
+        self.scores[color as usize][mov.from()][mov.to()].get()
+    }
+
+    pub fn get_countermove(&self, previous_move: Move) -> Move {
+        if previous_move.is_null() {
+            return Move::default();
+        }
+        self.countermoves[previous_move.from()][previous_move.to()].get()
+    }
+
+    // quiet promotions have no capture history
+    pub fn get_capture_score(&self, board: &Chessboard, mov: Move) -> i32 {
+        self.get_capture_entry(board, mov).map_or(0, |score| score.get())
+    }
+
+    // the capture caused a beta cutoff - the captures searched before it failed (malus)
+    pub fn update_capture_cutoff(&self, board: &Chessboard, depth_to_end: u8, mov: Move, failed_captures: &[Move]) {
+        let bonus = get_bonus(depth_to_end);
+        if let Some(score) = self.get_capture_entry(board, mov) {
+            add_bonus(score, bonus);
+        }
+        self.update_failed_captures(board, depth_to_end, failed_captures);
+    }
+
+    // a quiet move caused the cutoff - all captures searched before it failed
+    pub fn update_failed_captures(&self, board: &Chessboard, depth_to_end: u8, failed_captures: &[Move]) {
+        let bonus = get_bonus(depth_to_end);
+        for failed in failed_captures {
+            if let Some(score) = self.get_capture_entry(board, *failed) {
+                add_bonus(score, -bonus);
+            }
+        }
+    }
+
+    fn get_capture_entry(&self, board: &Chessboard, mov: Move) -> Option<&Cell<i32>> {
+        let captured = match mov.move_type() {
+            MoveType::EnPassant => Some(Piece::Pawn),
+            _ => board.get_type_of_figure(board.get_opponent_color(), mov.to()),
+        }?;
+        let piece = board.get_type_of_figure(board.current_move, mov.from())?;
+        Some(&self.captures[piece as usize][mov.to()][captured as usize])
+    }
+
+    // the quiet move caused a beta cutoff - the quiet moves searched before it failed (malus)
+    pub fn update_quiet_cutoff(
+        &self,
+        color: Color,
+        ply: usize,
+        depth_to_end: u8,
+        previous_move: Move,
+        mov: Move,
+        failed_quiets: &[Move],
+    ) {
+        if let Some(killers) = self.killers.get(ply) {
+            if killers[0].get() != mov {
+                killers[1].set(killers[0].get());
+                killers[0].set(mov);
+            }
+        }
+        if !previous_move.is_null() {
+            self.countermoves[previous_move.from()][previous_move.to()].set(mov);
+        }
+        let bonus = get_bonus(depth_to_end);
+        add_bonus(&self.scores[color as usize][mov.from()][mov.to()], bonus);
+        for failed in failed_quiets {
+            add_bonus(&self.scores[color as usize][failed.from()][failed.to()], -bonus);
+        }
+    }
+
+    // between searches - killers belong to the plies of the old search, scores are halved
+    // countermoves are kept, they don't depend on the root position
+    fn age(&self) {
+        for killers in &self.killers {
+            for killer in killers {
+                killer.set(Move::default());
+            }
+        }
+        for score in self.scores.iter().flatten().flatten() {
+            score.set(score.get() / 2);
+        }
+        for score in self.captures.iter().flatten().flatten() {
+            score.set(score.get() / 2);
+        }
+    }
+}
+
+fn get_bonus(depth_to_end: u8) -> i32 {
+    (depth_to_end as i32 * depth_to_end as i32).min(400)
+}
+
+// history gravity - the closer the score is to the bound, the smaller the change
+fn add_bonus(score: &Cell<i32>, bonus: i32) {
+    score.set(score.get() + bonus - score.get() * bonus.abs() / MAX_HISTORY);
+}
+
+pub fn start_new_search() {
+    SEARCH_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+// runs the search with the tables of the current thread - tables of a previous search are aged first
+pub fn with_thread_history<R>(search: impl FnOnce(&SearchHistory) -> R) -> R {
+    SEARCH_HISTORY.with(|history| {
+        let generation = SEARCH_GENERATION.load(Ordering::Relaxed);
+        if history.generation.get() != generation {
+            history.age();
+            history.generation.set(generation);
+        }
+        search(history)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cutoffs_update_the_tables() {
+        let history = SearchHistory::default();
+        let previous = Move::new(52, 36, MoveType::DoublePawnPush);
+        let cutoff = Move::new(6, 21, MoveType::Quiet);
+        let failed = Move::new(15, 23, MoveType::Quiet);
+        history.update_quiet_cutoff(Color::White, 3, 4, previous, cutoff, &[failed]);
+
+        assert_eq!([cutoff, Move::default()], history.get_killers(3));
+        assert_eq!([Move::default(); 2], history.get_killers(4));
+        assert_eq!(cutoff, history.get_countermove(previous));
+        assert_eq!(16, history.get_score(Color::White, cutoff));
+        assert_eq!(-16, history.get_score(Color::White, failed));
+        assert_eq!(0, history.get_score(Color::Black, cutoff));
+
+        // the same killer twice does not push out the other one
+        history.update_quiet_cutoff(Color::White, 3, 4, previous, failed, &[]);
+        history.update_quiet_cutoff(Color::White, 3, 4, previous, failed, &[]);
+        assert_eq!([failed, cutoff], history.get_killers(3));
+    }
+
+    #[test]
+    fn test_scores_stay_in_bounds_and_age() {
+        let history = SearchHistory::default();
+        let mov = Move::new(6, 21, MoveType::Quiet);
+        for _ in 0..1000 {
+            history.update_quiet_cutoff(Color::White, 0, 30, Move::default(), mov, &[]);
+        }
+        let score = history.get_score(Color::White, mov);
+        assert!(score > MAX_HISTORY / 2 && score <= MAX_HISTORY);
+
+        history.age();
+        assert_eq!(score / 2, history.get_score(Color::White, mov));
+        assert_eq!([Move::default(); 2], history.get_killers(0));
+    }
+
+    #[test]
+    fn test_capture_history() {
+        let history = SearchHistory::default();
+        let board = Chessboard::from_fen("4k3/8/8/3p4/2P1P3/8/8/3QK3 w - - 0 1");
+        let pawn_takes = board.get_move_from_uci_input("e4d5").unwrap();
+        let other_pawn_takes = board.get_move_from_uci_input("c4d5").unwrap();
+        let queen_takes = board.get_move_from_uci_input("d1d5").unwrap();
+        history.update_capture_cutoff(&board, 4, queen_takes, &[pawn_takes]);
+
+        assert_eq!(16, history.get_capture_score(&board, queen_takes));
+        // same piece on the same field takes the same victim
+        assert_eq!(-16, history.get_capture_score(&board, other_pawn_takes));
+        assert_eq!(0, history.get_capture_score(&board, board.get_move_from_uci_input("d1d2").unwrap()));
+
+        history.update_failed_captures(&board, 4, &[queen_takes]);
+        assert!(history.get_capture_score(&board, queen_takes) < 16);
+    }
+}
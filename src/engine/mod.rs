@@ -1,7 +1,11 @@
+pub mod bench;
 pub mod engine;
+pub mod history;
 pub mod sender;
-pub mod ray;
-pub mod checked;
+pub mod attacks;
 pub mod moves;
-pub mod count;
+pub mod move_picker;
+pub mod perft;
+pub mod pv;
+pub mod see;
 pub mod transposition;
\ No newline at end of file
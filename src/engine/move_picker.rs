@@ -0,0 +1,455 @@
+use smallvec::SmallVec;
+
+use crate::{
+    board::{board::Chessboard, chess_move::{Move, MoveType}, promotion::{convert_promotion_to_figure, Promotion}},
+    figures::piece::{Piece, PIECE_VALUES},
+};
+
+use super::{
+    moves::{
+        get_captures, get_evasions, get_quiet_checks, get_quiet_moves, is_legal, is_not_pinned, Threats,
+    },
+    history::SearchHistory,
+    see::see_ge,
+};
+
+// order in which the moves are returned - every stage is only generated once it is reached
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Stage {
+    TranspositionMove,
+    GenerateCaptures,
+    GoodCaptures,
+    Killers,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    GenerateQuietChecks,
+    QuietChecks,
+    GenerateEvasions,
+    Evasions,
+    Done,
+}
+
+// capture history is at most 16384 - divided it stays below the difference of two victims in the MVV-LVA score (200)
+const CAPTURE_HISTORY_DIVISOR: i32 = 128;
+
+#[derive(Clone, Copy)]
+struct ScoredMove {
+    mov: Move,
+    score: i32,
+}
+
+// returns the legal moves of a position - most promising first, so cutoffs skip most of the generation
+// TT move, good captures (MVV-LVA and capture history), killers and countermove, quiets (by history), bad captures
+// if we are in check all evasions are generated at once
+// quiescence search: TT move, good captures and optionally silent checks
+pub struct MovePicker<'a> {
+    board: &'a Chessboard,
+    threats: Threats,
+    transposition_move: Move,
+    // killers followed by the countermove
+    refutations: [Move; 3],
+    history: Option<&'a SearchHistory>,
+    only_captures: bool,
+    with_quiet_checks: bool,
+    stage: Stage,
+    moves: SmallVec<[ScoredMove; 64]>,
+    bad_captures: SmallVec<[ScoredMove; 64]>,
+    index: usize,
+    refutation_index: usize,
+}
+
+impl<'a> MovePicker<'a> {
+    pub fn new(
+        board: &'a Chessboard,
+        threats: Threats,
+        transposition_move: Move,
+        killers: [Move; 2],
+        countermove: Move,
+        history: Option<&'a SearchHistory>,
+    ) -> MovePicker<'a> {
+        MovePicker {
+            board,
+            threats,
+            transposition_move,
+            refutations: [killers[0], killers[1], countermove],
+            history,
+            only_captures: false,
+            with_quiet_checks: false,
+            stage: Stage::TranspositionMove,
+            moves: SmallVec::new(),
+            bad_captures: SmallVec::new(),
+            index: 0,
+            refutation_index: 0,
+        }
+    }
+
+    // only captures and promotions (all evasions if we are in check)
+    pub fn new_captures(
+        board: &'a Chessboard,
+        threats: Threats,
+        transposition_move: Move,
+        history: Option<&'a SearchHistory>,
+    ) -> MovePicker<'a> {
+        let mut move_picker = MovePicker::new(board, threats, transposition_move, [Move::default(); 2], Move::default(), history);
+        move_picker.only_captures = true;
+        move_picker
+    }
+
+    // captures and promotions followed by the silent moves giving check (first ply of the quiescence search)
+    pub fn new_captures_and_checks(
+        board: &'a Chessboard,
+        threats: Threats,
+        transposition_move: Move,
+        history: Option<&'a SearchHistory>,
+    ) -> MovePicker<'a> {
+        let mut move_picker = MovePicker::new_captures(board, threats, transposition_move, history);
+        move_picker.with_quiet_checks = true;
+        move_picker
+    }
+
+    pub fn is_in_check(&self) -> bool {
+        self.threats.is_in_check
+    }
+
+    #[cfg(test)]
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    fn is_valid_transposition_move(&self) -> bool {
+        let mov = self.transposition_move;
+        if self.only_captures && !mov.is_capture() && !mov.is_promotion() {
+            return false;
+        }
+        is_legal(self.board, &self.threats, mov)
+    }
+
+    fn is_valid_refutation(&self, index: usize) -> bool {
+        let mov = self.refutations[index];
+        // captures and promotions are already returned with the captures
+        mov != self.transposition_move
+            && !self.refutations[..index].contains(&mov)
+            && !mov.is_capture()
+            && !mov.is_promotion()
+            && is_legal(self.board, &self.threats, mov)
+    }
+
+    fn set_moves(&mut self, moves: SmallVec<[Move; 64]>, score: impl Fn(&MovePicker, Move) -> i32) {
+        let mut scored_moves = SmallVec::new();
+        for mov in moves {
+            if is_not_pinned(&self.threats, &mov) {
+                scored_moves.push(ScoredMove { mov, score: score(self, mov) });
+            }
+        }
+        self.moves = scored_moves;
+        self.index = 0;
+    }
+
+    // selection sort - only the moves we actually search are sorted
+    fn pick_best(&mut self) -> Option<Move> {
+        if self.index >= self.moves.len() {
+            return None;
+        }
+        let mut best = self.index;
+        for current in self.index + 1..self.moves.len() {
+            if self.moves[current].score > self.moves[best].score {
+                best = current;
+            }
+        }
+        self.moves.swap(self.index, best);
+        self.index += 1;
+        Some(self.moves[self.index - 1].mov)
+    }
+
+    fn generate_captures(&mut self) {
+        let mut moves = SmallVec::new();
+        get_captures(self.board, &self.threats, &mut moves);
+        self.set_moves(moves, |move_picker, mov| move_picker.get_capture_score(mov));
+        // losing captures (e.g. queen takes defended pawn) and underpromotions are tried after the quiet moves
+        let (good_captures, bad_captures) = self.moves.iter().copied().partition(|scored| self.is_good_capture(scored.mov));
+        self.moves = good_captures;
+        self.bad_captures = bad_captures;
+    }
+
+    fn generate_quiets(&mut self) {
+        let mut moves = SmallVec::new();
+        get_quiet_moves(self.board, &self.threats, &mut moves);
+        self.set_moves(moves, |move_picker, mov| move_picker.get_history_score(mov));
+    }
+
+    fn generate_quiet_checks(&mut self) {
+        let mut moves = SmallVec::new();
+        get_quiet_checks(self.board, &self.threats, &mut moves);
+        self.set_moves(moves, |move_picker, mov| move_picker.get_history_score(mov));
+    }
+
+    fn generate_evasions(&mut self) {
+        let moves = get_evasions(self.board, &self.threats);
+        self.set_moves(moves, |move_picker, mov| {
+            if mov == move_picker.transposition_move {
+                i32::MAX
+            } else if mov.is_capture() || mov.is_promotion() {
+                // always before the quiet moves
+                1 << 20 | move_picker.get_capture_score(mov)
+            } else {
+                move_picker.get_history_score(mov)
+            }
+        });
+    }
+
+    // MVV-LVA - most valuable victim first, least valuable attacker on equal victims
+    // the capture history only changes the order of captures with the same victim
+    fn get_capture_score(&self, mov: Move) -> i32 {
+        let victim = match mov.move_type() {
+            MoveType::EnPassant => PIECE_VALUES[Piece::Pawn as usize],
+            _ => self
+                .board
+                .get_type_of_figure(self.board.get_opponent_color(), mov.to())
+                .map_or(0, |piece| PIECE_VALUES[piece as usize]),
+        };
+        let promotion = mov
+            .promoted_to()
+            .map_or(0, |promoted_to| PIECE_VALUES[convert_promotion_to_figure(promoted_to) as usize]);
+        let history = self.history.map_or(0, |history| history.get_capture_score(self.board, mov));
+        10 * (victim + promotion) - self.get_attacker_value(mov) + history / CAPTURE_HISTORY_DIVISOR
+    }
+
+    fn get_attacker_value(&self, mov: Move) -> i32 {
+        self.board
+            .get_type_of_figure(self.board.current_move, mov.from())
+            .map_or(0, |piece| PIECE_VALUES[piece as usize])
+    }
+
+    // captures which don't lose material in the exchange - underpromotions are always tried late
+    fn is_good_capture(&self, mov: Move) -> bool {
+        if mov.promoted_to().is_some_and(|promoted_to| promoted_to != Promotion::Queen) {
+            return false;
+        }
+        see_ge(self.board, mov, 0)
+    }
+
+    fn get_history_score(&self, mov: Move) -> i32 {
+        self.history.map_or(0, |history| history.get_score(self.board.current_move, mov))
+    }
+}
+
+impl Iterator for MovePicker<'_> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::TranspositionMove => {
+                    self.stage = if self.threats.is_in_check {
+                        Stage::GenerateEvasions
+                    } else {
+                        Stage::GenerateCaptures
+                    };
+                    if self.threats.is_in_check {
+                        continue;
+                    }
+                    if self.is_valid_transposition_move() {
+                        return Some(self.transposition_move);
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    self.generate_captures();
+                    self.stage = Stage::GoodCaptures;
+                }
+                Stage::GoodCaptures => match self.pick_best() {
+                    Some(mov) if mov == self.transposition_move => {}
+                    Some(mov) => return Some(mov),
+                    // quiescence search - losing captures are pruned
+                    None if self.with_quiet_checks => self.stage = Stage::GenerateQuietChecks,
+                    None if self.only_captures => self.stage = Stage::Done,
+                    None => self.stage = Stage::Killers,
+                },
+                Stage::Killers => {
+                    if self.refutation_index < self.refutations.len() {
+                        let index = self.refutation_index;
+                        self.refutation_index += 1;
+                        if self.is_valid_refutation(index) {
+                            return Some(self.refutations[index]);
+                        }
+                        continue;
+                    }
+                    self.stage = Stage::GenerateQuiets;
+                }
+                Stage::GenerateQuiets => {
+                    self.generate_quiets();
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => match self.pick_best() {
+                    Some(mov) if mov == self.transposition_move || self.refutations.contains(&mov) => {}
+                    Some(mov) => return Some(mov),
+                    None => {
+                        self.stage = Stage::BadCaptures;
+                        self.moves = std::mem::take(&mut self.bad_captures);
+                        self.index = 0;
+                    }
+                },
+                Stage::BadCaptures => match self.pick_best() {
+                    Some(mov) if mov == self.transposition_move => {}
+                    Some(mov) => return Some(mov),
+                    None => self.stage = Stage::Done,
+                },
+                Stage::GenerateQuietChecks => {
+                    self.generate_quiet_checks();
+                    self.stage = Stage::QuietChecks;
+                }
+                // a silent transposition move is never returned first in the quiescence search
+                Stage::QuietChecks => match self.pick_best() {
+                    Some(mov) => return Some(mov),
+                    None => self.stage = Stage::Done,
+                },
+                Stage::GenerateEvasions => {
+                    self.generate_evasions();
+                    self.stage = Stage::Evasions;
+                }
+                Stage::Evasions => match self.pick_best() {
+                    Some(mov) => return Some(mov),
+                    None => self.stage = Stage::Done,
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::epd::parse_epd_suite,
+        engine::{
+            moves::{get_threats, get_valid_moves_in_position},
+            sender::convert_move_to_uci,
+        },
+    };
+
+    fn check_same_moves(board: &Chessboard, depth: u8, foreign_moves: &[Move]) {
+        let (mut expected, _) = get_valid_moves_in_position(board, true);
+        // moves of other positions are used as tt move and killers - they must not be returned if they are not legal here
+        let killers = [foreign_moves[0], foreign_moves[1]];
+        let mut picked: Vec<Move> = MovePicker::new(board, get_threats(board), foreign_moves[2], killers, foreign_moves[3], None).collect();
+        expected.sort_by_key(|mov| format!("{:?}", mov));
+        picked.sort_by_key(|mov| format!("{:?}", mov));
+        assert_eq!(expected.to_vec(), picked);
+
+        // losing captures are not returned in the quiescence search
+        let (mut expected_captures, is_in_check) = get_valid_moves_in_position(board, false);
+        if !is_in_check {
+            expected_captures.retain(|mov| {
+                mov.promoted_to().is_none_or(|promoted_to| promoted_to == Promotion::Queen) && see_ge(board, *mov, 0)
+            });
+        }
+        let mut picked_captures: Vec<Move> = MovePicker::new_captures(board, get_threats(board), foreign_moves[2], None).collect();
+        expected_captures.sort_by_key(|mov| format!("{:?}", mov));
+        picked_captures.sort_by_key(|mov| format!("{:?}", mov));
+        assert_eq!(expected_captures.to_vec(), picked_captures);
+
+        if depth == 0 {
+            return;
+        }
+        let (moves, _) = get_valid_moves_in_position(board, true);
+        for (index, single) in moves.iter().enumerate() {
+            let mut new_board = *board;
+            new_board.move_figure(*single);
+            // neighbouring moves of the parent are good candidates for illegal moves
+            let foreign = [
+                *single,
+                moves[(index + 1) % moves.len()],
+                moves[(index + 2) % moves.len()],
+                moves[(index + 3) % moves.len()],
+            ];
+            check_same_moves(&new_board, depth - 1, &foreign);
+        }
+    }
+
+    #[test]
+    fn test_returns_all_legal_moves_once() {
+        for epd in parse_epd_suite(include_str!("../../suites/perft.epd")) {
+            check_same_moves(&epd.create_board(), 2, &[Move::default(); 4]);
+        }
+    }
+
+    #[test]
+    fn test_stages_in_order() {
+        // both takes on e5 win a pawn - the pawn as least valuable attacker takes first
+        let board = Chessboard::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/3PP3/5N2/PPP2PPP/RNBQKB1R w KQkq - 0 1");
+        let transposition_move = board.get_move_from_uci_input("f1c4").unwrap();
+        let killer = board.get_move_from_uci_input("b1c3").unwrap();
+        let countermove = board.get_move_from_uci_input("a2a3").unwrap();
+        let history = SearchHistory::default();
+        let h2h3 = board.get_move_from_uci_input("h2h3").unwrap();
+        history.update_quiet_cutoff(board.current_move, 0, 10, Move::default(), h2h3, &[]);
+
+        let killers = [killer, Move::default()];
+        let mut move_picker = MovePicker::new(&board, get_threats(&board), transposition_move, killers, countermove, Some(&history));
+        assert_eq!(Some(transposition_move), move_picker.next());
+        // nothing is generated before it is needed
+        assert_eq!(Stage::GenerateCaptures, move_picker.stage());
+
+        let moves: Vec<String> = move_picker.map(convert_move_to_uci).collect();
+        assert_eq!(vec!["d4e5", "f3e5", "b1c3", "a2a3", "h2h3"], moves[..5]);
+        assert!(!moves.contains(&String::from("f1c4")));
+        assert_eq!(1, moves.iter().filter(|mov| *mov == "b1c3").count());
+        assert_eq!(1, moves.iter().filter(|mov| *mov == "a2a3").count());
+        let (all_moves, _) = get_valid_moves_in_position(&board, true);
+        assert_eq!(all_moves.len(), moves.len() + 1);
+    }
+
+    #[test]
+    fn test_capture_history_orders_equal_victims() {
+        let board = Chessboard::from_fen("4k3/8/8/3p1p2/4P3/8/8/4K3 w - - 0 1");
+        let history = SearchHistory::default();
+        for capture in ["e4f5", "e4d5"] {
+            let mov = board.get_move_from_uci_input(capture).unwrap();
+            history.update_capture_cutoff(&board, 20, mov, &[]);
+            let mut move_picker = MovePicker::new_captures(&board, get_threats(&board), Move::default(), Some(&history));
+            assert_eq!(Some(mov), move_picker.next());
+            history.update_failed_captures(&board, 20, &[mov]);
+        }
+        // a better victim is still first
+        let board = Chessboard::from_fen("4k3/8/8/3q1p2/4P3/8/8/4K3 w - - 0 1");
+        let pawn = board.get_move_from_uci_input("e4f5").unwrap();
+        for _ in 0..100 {
+            history.update_capture_cutoff(&board, 20, pawn, &[]);
+        }
+        let mut move_picker = MovePicker::new_captures(&board, get_threats(&board), Move::default(), Some(&history));
+        assert_eq!(Some(board.get_move_from_uci_input("e4d5").unwrap()), move_picker.next());
+    }
+
+    #[test]
+    fn test_bad_captures_last() {
+        // Qxd7 only takes a pawn and loses the queen
+        let board = Chessboard::from_fen("4k3/3p4/8/8/8/8/8/3QK3 w - - 0 1");
+        let moves: Vec<Move> = MovePicker::new(&board, get_threats(&board), Move::default(), [Move::default(); 2], Move::default(), None).collect();
+        assert_eq!(Some(&board.get_move_from_uci_input("d1d7").unwrap()), moves.last());
+
+        // and is not tried in the quiescence search
+        let captures: Vec<Move> = MovePicker::new_captures(&board, get_threats(&board), Move::default(), None).collect();
+        assert!(captures.is_empty());
+    }
+
+    #[test]
+    fn test_quiet_checks_after_captures() {
+        let board = Chessboard::from_fen("4k3/8/3p4/8/4N3/8/8/4K3 w - - 0 1");
+        let moves: Vec<String> = MovePicker::new_captures_and_checks(&board, get_threats(&board), Move::default(), None).map(convert_move_to_uci).collect();
+        assert_eq!(vec!["e4d6", "e4f6"], moves);
+        assert_eq!(1, MovePicker::new_captures(&board, get_threats(&board), Move::default(), None).count());
+    }
+
+    #[test]
+    fn test_evasions() {
+        // in check - transposition move first, then the capture of the checking piece
+        let board = Chessboard::from_fen("4k3/8/8/8/8/8/3q4/R3K3 w - - 0 1");
+        let transposition_move = board.get_move_from_uci_input("e1f1").unwrap();
+        let mut move_picker = MovePicker::new(&board, get_threats(&board), transposition_move, [Move::default(); 2], Move::default(), None);
+        assert!(move_picker.is_in_check());
+        assert_eq!(Some(transposition_move), move_picker.next());
+        assert_eq!(Some(board.get_move_from_uci_input("e1d2").unwrap()), move_picker.next());
+        assert_eq!(0, move_picker.count());
+    }
+}
@@ -1,25 +1,20 @@
 use smallvec::SmallVec;
 
 use crate::{
-    board::{bitboard::Bitboard, board::Chessboard},
+    board::{bitboard::{line, Bitboard}, board::Chessboard, chess_move::Move},
     figures::{
-        color::Color,
-        king::{
-            get_all_king_moves_in_check, get_fields_threatened_by_king, get_possible_king_moves,
-            get_possible_king_takes,
-        },
+        king::{get_all_king_moves_in_check, get_possible_king_moves, get_possible_king_takes},
         knight::{
-            get_fields_threatened_by_knight, get_possible_knight_moves,
-            get_possible_knight_moves_to_prevent_check, get_possible_knight_takes,
+            get_possible_knight_moves, get_possible_knight_moves_to_prevent_check,
+            get_possible_knight_takes,
         },
         pawn::{
-            get_fields_threatened_by_pawn, get_possible_pawn_moves,
-            get_possible_pawn_moves_to_prevent_check, get_possible_pawn_takes_and_promotion,
+            get_possible_pawn_moves, get_possible_pawn_moves_to_prevent_check,
+            get_possible_pawn_takes_and_promotion,
         },
         piece::Piece,
         sliding_moves::{
-            get_fields_threatened_by_bishop, get_fields_threatened_by_queen,
-            get_fields_threatened_by_rook, get_possible_bishop_moves,
+            get_possible_bishop_moves,
             get_possible_bishop_moves_to_prevent_check, get_possible_bishop_takes,
             get_possible_queen_moves, get_possible_queen_moves_to_prevent_check,
             get_possible_queen_takes, get_possible_rook_moves,
@@ -28,111 +23,63 @@ use crate::{
     },
 };
 
-use super::{
-    checked::get_fields_to_prevent_check,
-    engine::PossibleMove,
-    ray::get_pinned_pieces_and_possible_moves,
-    transposition::{table::{get_entry_without_check}, transposition::Transposition},
+use super::attacks::{
+    get_attacked_fields, get_check_block_mask, get_check_fields, get_checkers, get_king_blockers,
+    get_pinned_pieces,
 };
 
 pub fn get_valid_moves_in_position(
     board: &Chessboard,
     calculate_all_moves: bool,
-) -> (SmallVec<[PossibleMove; 64]>, bool) {
+) -> (SmallVec<[Move; 64]>, bool) {
+    let threats = get_threats(board);
+    let mut moves: SmallVec<[Move; 64]> = SmallVec::new();
+    if threats.is_in_check {
+        moves = get_evasions(board, &threats);
+    } else {
+        get_captures(board, &threats, &mut moves);
+        if calculate_all_moves {
+            get_quiet_moves(board, &threats, &mut moves);
+        }
+    }
+    let not_pinned_moves: SmallVec<[Move; 64]> = get_not_pinned_pieces(&threats, moves);
+    (not_pinned_moves, threats.is_in_check)
+}
+
+// attacks of the opponent, checks and pins of the side to move - computed once per node, every generation needs them
+#[derive(Clone, Copy, Debug)]
+pub struct Threats {
+    pub king_position: usize,
+    pub opponent_moves: Bitboard,
+    pub pinned: Bitboard,
+    // fields our pieces (not the king) can move to - all fields if we are not in check
+    pub check_block: Bitboard,
+    pub is_in_check: bool,
+    pub is_in_double_check: bool,
+}
+
+pub fn get_threats(board: &Chessboard) -> Threats {
     let king_position = board
         .get_pieces(board.current_move, Piece::King)
         .get_first_field();
-    // get moves from opponent - we ignore our own king position for rook/bishop/queen to standing on d8, and going to c8 to prevent check from h8
-    let (opponent_moves, count_of_checks) =
-        get_all_threatened_fields(board, board.get_opponent_color(), king_position);
-    // if opponent moves include own king -> we are in check
-    let is_in_check = opponent_moves.field_is_used(king_position);
-    let is_in_double_check = is_in_check && count_of_checks > 1;
-
-    let moves: SmallVec<[PossibleMove; 64]> = get_all_possible_moves(
-        board,
-        board.current_move,
+    // we ignore our own king for the sliders - standing on d8 and going to c8 does not prevent a check from h8
+    let mut without_king = board.positions;
+    without_king.remove_field(king_position);
+    let opponent_moves = get_attacked_fields(board, board.get_opponent_color(), without_king);
+    let checkers = get_checkers(board, king_position);
+    Threats {
+        king_position,
         opponent_moves,
-        is_in_check,
-        is_in_double_check,
-        calculate_all_moves,
-    );
-    let not_pinned_moves: SmallVec<[PossibleMove; 64]> = get_not_pinned_pieces(board, &king_position, moves);
-    (not_pinned_moves, is_in_check)
-}
-
-// get all fields threadned (ignore if opponent figure is on field)
-fn get_all_threatened_fields(
-    board: &Chessboard,
-    color: Color,
-    king_position: usize,
-) -> (Bitboard, u8) {
-    let mut moves = Bitboard::new();
-    let mut cound_of_checks = 0;
-
-    let pawn_positions = board.get_pieces(color, Piece::Pawn);
-    pawn_positions.iterate_board(|position| {
-        let pawn_moves = get_fields_threatened_by_pawn(position, color);
-        moves.board |= pawn_moves.board;
-
-        if pawn_moves.field_is_used(king_position) {
-            cound_of_checks += 1;
-        }
-    });
-
-    let rook_positions: &Bitboard = board.get_pieces(color, Piece::Rook);
-    rook_positions.iterate_board(|position| {
-        let rook_moves = get_fields_threatened_by_rook(board, position, king_position);
-        moves.board |= rook_moves.board;
-
-        if rook_moves.field_is_used(king_position) {
-            cound_of_checks += 1;
-        }
-    });
-
-    let bishop_positions = board.get_pieces(color, Piece::Bishop);
-    bishop_positions.iterate_board(|position| {
-        let bishop_moves = get_fields_threatened_by_bishop(board, position, king_position);
-        moves.board |= bishop_moves.board;
-
-        if bishop_moves.field_is_used(king_position) {
-            cound_of_checks += 1;
-        }
-    });
-
-    let queen_positions = board.get_pieces(color, Piece::Queen);
-    queen_positions.iterate_board(|position| {
-        let queen_moves = get_fields_threatened_by_queen(board, position, king_position);
-        moves.board |= queen_moves.board;
-
-        if queen_moves.field_is_used(king_position) {
-            cound_of_checks += 1;
-        }
-    });
-
-    let knight_positions = board.get_pieces(color, Piece::Knight);
-    knight_positions.iterate_board(|position| {
-        let knight_moves = get_fields_threatened_by_knight(position);
-        moves.board |= knight_moves.board;
-
-        if knight_moves.field_is_used(king_position) {
-            cound_of_checks += 1;
-        }
-    });
-
-    let king_position = board.get_pieces(color, Piece::King).get_first_field();
-    moves.board |= get_fields_threatened_by_king(king_position).board;
-    (moves, cound_of_checks)
+        pinned: get_pinned_pieces(board, king_position),
+        check_block: get_check_block_mask(king_position, checkers),
+        is_in_check: !checkers.is_empty(),
+        is_in_double_check: checkers.len() > 1,
+    }
 }
 
-fn get_all_prevent_check_moves(
-    board: &Chessboard,
-    color: Color,
-    opponent_moves: Bitboard,
-) -> SmallVec<[PossibleMove; 64]> {
-    let king_position = board.get_pieces(color, Piece::King).get_first_field();
-    let prevent_check_fields = get_fields_to_prevent_check(board, king_position, opponent_moves);
-
+fn get_all_prevent_check_moves(board: &Chessboard, threats: &Threats) -> SmallVec<[Move; 64]> {
+    let color = board.current_move;
+    let prevent_check_fields = threats.check_block;
     let mut moves = SmallVec::new();
 
     let bishop_positions = board.get_pieces(color, Piece::Bishop);
@@ -157,7 +104,7 @@ fn get_all_prevent_check_moves(
 
     let knight_positions = board.get_pieces(color, Piece::Knight);
     knight_positions.iterate_board(|position| {
-        get_possible_knight_moves_to_prevent_check(position, prevent_check_fields, &mut moves);
+        get_possible_knight_moves_to_prevent_check(board, position, prevent_check_fields, &mut moves);
     });
 
     let rook_positions = board.get_pieces(color, Piece::Rook);
@@ -170,8 +117,7 @@ fn get_all_prevent_check_moves(
         );
     });
 
-    let king_position = board.get_pieces(color, Piece::King).get_first_field();
-    get_all_king_moves_in_check(board, king_position, opponent_moves, &mut moves);
+    get_all_king_moves_in_check(board, threats.king_position, threats.opponent_moves, &mut moves);
 
     let pawn_positions = board.get_pieces(color, Piece::Pawn);
     pawn_positions.iterate_board(|position| {
@@ -186,125 +132,240 @@ fn get_all_prevent_check_moves(
 
     moves
 }
-// default logic get all pseudo legal moves
-fn get_all_possible_moves(
-    board: &Chessboard,
-    color: Color,
-    opponent_moves: Bitboard,
-    is_in_check: bool,
-    is_in_double_check: bool,
-    get_all_moves: bool,
-) -> SmallVec<[PossibleMove; 64]> {
-    let mut moves = SmallVec::new();
+// all pseudo legal moves if we are in check
+pub fn get_evasions(board: &Chessboard, threats: &Threats) -> SmallVec<[Move; 64]> {
     // if we are in double check only moving the king can save us
-    if is_in_double_check {
-        let king_position = board.get_pieces(color, Piece::King).get_first_field();
-        get_all_king_moves_in_check(board, king_position, opponent_moves, &mut moves);
+    if threats.is_in_double_check {
+        let mut moves = SmallVec::new();
+        get_all_king_moves_in_check(board, threats.king_position, threats.opponent_moves, &mut moves);
         return moves;
     }
-    if is_in_check {
-        return get_all_prevent_check_moves(board, color, opponent_moves);
-    }
-
-    let bishop_positions = board.get_pieces(color, Piece::Bishop);
-    let queen_positions = board.get_pieces(color, Piece::Queen);
-    let knight_positions = board.get_pieces(color, Piece::Knight);
-    let rook_positions = board.get_pieces(color, Piece::Rook);
-    let king_position = board.get_pieces(color, Piece::King).get_first_field();
-    let pawn_positions = board.get_pieces(color, Piece::Pawn);
+    get_all_prevent_check_moves(board, threats)
+}
 
-    // add all takes to possible Moves
-    pawn_positions.iterate_board(|position| {
-        get_possible_pawn_takes_and_promotion(board, position, color, &mut moves);
+// pseudo legal takes, en passant and promotions - only if we are not in check
+pub fn get_captures(board: &Chessboard, threats: &Threats, moves: &mut SmallVec<[Move; 64]>) {
+    let color = board.current_move;
+    board.get_pieces(color, Piece::Pawn).iterate_board(|position| {
+        get_possible_pawn_takes_and_promotion(board, position, color, moves);
     });
 
-    rook_positions.iterate_board(|position| {
-        get_possible_rook_takes(board, position, &mut moves);
+    board.get_pieces(color, Piece::Rook).iterate_board(|position| {
+        get_possible_rook_takes(board, position, moves);
     });
 
-    bishop_positions.iterate_board(|position| {
-        get_possible_bishop_takes(board, position, &mut moves);
+    board.get_pieces(color, Piece::Bishop).iterate_board(|position| {
+        get_possible_bishop_takes(board, position, moves);
     });
 
-    queen_positions.iterate_board(|position| {
-        get_possible_queen_takes(board, position, &mut moves);
+    board.get_pieces(color, Piece::Queen).iterate_board(|position| {
+        get_possible_queen_takes(board, position, moves);
     });
 
-    knight_positions.iterate_board(|position| {
-        get_possible_knight_takes(board, position, &mut moves);
+    board.get_pieces(color, Piece::Knight).iterate_board(|position| {
+        get_possible_knight_takes(board, position, moves);
     });
-    get_possible_king_takes(board, king_position, opponent_moves, &mut moves);
-
-    let prev_best_move_opt = get_entry_without_check(board.zobrist_key);
-    // we only want some moves which we think should be calculated
-    if !get_all_moves {
-        add_prev_best_move_as_first_move(&mut moves, prev_best_move_opt);
-        return moves;
-    }
+    get_possible_king_takes(board, threats.king_position, threats.opponent_moves, moves);
+}
 
-    // add all silent moves (not takes) at the end
-    bishop_positions.iterate_board(|position| {
-        get_possible_bishop_moves(board, position, &mut moves);
+// pseudo legal silent moves (not takes) and castles - only if we are not in check
+pub fn get_quiet_moves(board: &Chessboard, threats: &Threats, moves: &mut SmallVec<[Move; 64]>) {
+    let color = board.current_move;
+    board.get_pieces(color, Piece::Bishop).iterate_board(|position| {
+        get_possible_bishop_moves(board, position, moves);
     });
 
-    queen_positions.iterate_board(|position| {
-        get_possible_queen_moves(board, position, &mut moves);
+    board.get_pieces(color, Piece::Queen).iterate_board(|position| {
+        get_possible_queen_moves(board, position, moves);
     });
 
-    rook_positions.iterate_board(|position| {
-        get_possible_rook_moves(board, position, &mut moves);
+    board.get_pieces(color, Piece::Rook).iterate_board(|position| {
+        get_possible_rook_moves(board, position, moves);
     });
 
-    knight_positions.iterate_board(|position| {
-        get_possible_knight_moves(board, position, &mut moves);
+    board.get_pieces(color, Piece::Knight).iterate_board(|position| {
+        get_possible_knight_moves(board, position, moves);
     });
 
-    get_possible_king_moves(board, king_position, color, opponent_moves, &mut moves);
+    get_possible_king_moves(board, threats.king_position, color, threats.opponent_moves, moves);
 
-    pawn_positions.iterate_board(|position| {
-        get_possible_pawn_moves(board, position, color, &mut moves);
+    board.get_pieces(color, Piece::Pawn).iterate_board(|position| {
+        get_possible_pawn_moves(board, position, color, moves);
     });
-    add_prev_best_move_as_first_move(&mut moves, prev_best_move_opt);
+}
+
+// legal silent moves giving check (direct or by moving a piece out of the way of our slider) - only if we are not in check
+// castles are left out, they check very rarely
+pub fn get_quiet_checks(board: &Chessboard, threats: &Threats, moves: &mut SmallVec<[Move; 64]>) {
+    let opponent_king = board.get_pieces(board.get_opponent_color(), Piece::King).get_first_field();
+    let check_fields = get_check_fields(board, opponent_king);
+    let discovering_pieces =
+        get_king_blockers(board, opponent_king, board.current_move) & *board.get_positions_by_current_player();
+
+    let mut quiet_moves = SmallVec::new();
+    get_quiet_moves(board, threats, &mut quiet_moves);
+    for mov in quiet_moves {
+        if mov.is_castle() || !is_not_pinned(threats, &mov) {
+            continue;
+        }
+        let Some(piece) = board.get_type_of_figure(board.current_move, mov.from()) else {
+            continue;
+        };
+        let is_direct_check = check_fields[piece as usize].field_is_used(mov.to());
+        let is_discovered_check = discovering_pieces.field_is_used(mov.from())
+            && !line(opponent_king, mov.from()).field_is_used(mov.to());
+        if is_direct_check || is_discovered_check {
+            moves.push(mov);
+        }
+    }
+}
+
+// pseudo legal moves of our piece on the given field - only if we are not in check
+// used to check moves we did not generate (transposition table, killers) without generating all moves
+pub fn get_moves_of_piece(board: &Chessboard, threats: &Threats, position: usize) -> SmallVec<[Move; 64]> {
+    let color = board.current_move;
+    let mut moves = SmallVec::new();
+    match board.get_type_of_figure(color, position) {
+        Some(Piece::Pawn) => {
+            get_possible_pawn_takes_and_promotion(board, position, color, &mut moves);
+            get_possible_pawn_moves(board, position, color, &mut moves);
+        }
+        Some(Piece::Knight) => {
+            get_possible_knight_takes(board, position, &mut moves);
+            get_possible_knight_moves(board, position, &mut moves);
+        }
+        Some(Piece::Bishop) => {
+            get_possible_bishop_takes(board, position, &mut moves);
+            get_possible_bishop_moves(board, position, &mut moves);
+        }
+        Some(Piece::Rook) => {
+            get_possible_rook_takes(board, position, &mut moves);
+            get_possible_rook_moves(board, position, &mut moves);
+        }
+        Some(Piece::Queen) => {
+            get_possible_queen_takes(board, position, &mut moves);
+            get_possible_queen_moves(board, position, &mut moves);
+        }
+        Some(Piece::King) => {
+            get_possible_king_takes(board, position, threats.opponent_moves, &mut moves);
+            get_possible_king_moves(board, position, color, threats.opponent_moves, &mut moves);
+        }
+        None => {}
+    }
     moves
 }
 
-fn add_prev_best_move_as_first_move(
-    moves: &mut SmallVec<[PossibleMove; 64]>,
-    prev_best_move_opt: Option<Transposition>,
-) {
-    if prev_best_move_opt.is_none() {
-        return;
+// moves we did not generate (transposition table, killers) could be from another position with the same key
+// pseudo legal - our piece can make the move, but it could be pinned
+pub fn is_pseudo_legal(board: &Chessboard, threats: &Threats, mov: Move) -> bool {
+    if mov.is_null() {
+        return false;
     }
-    // order to first position (or add previous best move)
-    // null checked before
-    let prev_best_move = prev_best_move_opt.unwrap().best_move;
-    if let Some(pos) = moves.iter().position(|single| {
-        single.from == prev_best_move.from
-            && single.to == prev_best_move.to
-            && single.promoted_to == prev_best_move.promoted_to
-    }) {
-        moves.remove(pos);
+    if threats.is_in_check {
+        return get_evasions(board, threats).contains(&mov);
     }
-    moves.insert(0, prev_best_move);
+    get_moves_of_piece(board, threats, mov.from()).contains(&mov)
 }
 
-fn get_not_pinned_pieces(
-    board: &Chessboard,
-    king_position: &usize,
-    moves: SmallVec<[PossibleMove; 64]>,
-) -> SmallVec<[PossibleMove; 64]> {
-    let pinned_pieces = get_pinned_pieces_and_possible_moves(board, king_position);
+pub fn is_legal(board: &Chessboard, threats: &Threats, mov: Move) -> bool {
+    is_pseudo_legal(board, threats, mov) && is_not_pinned(threats, &mov)
+}
 
-    if pinned_pieces.is_empty() {
+// pinned pieces can only move on the line to the pinner (e.g. Pinned Rook can capture pinning Rook)
+pub fn is_not_pinned(threats: &Threats, mov: &Move) -> bool {
+    !threats.pinned.field_is_used(mov.from()) || line(threats.king_position, mov.from()).field_is_used(mov.to())
+}
+
+fn get_not_pinned_pieces(threats: &Threats, moves: SmallVec<[Move; 64]>) -> SmallVec<[Move; 64]> {
+    if threats.pinned.is_empty() {
         return moves;
     }
-    // filter out all moves from pinned pieces - but keep the moves on the same "line" as pinner (e.g. Pinned Rook can capture pinning Rook)
+    // filter out all moves from pinned pieces - but keep the moves on the same "line" as pinner
     moves
         .into_iter()
-        // we have estabilshed, that key is defined (unwrap)
-        .filter(|mov| {
-            !pinned_pieces.contains_key(&mov.from)
-                || pinned_pieces.get(&mov.from).unwrap().contains(&mov.to)
-        })
+        .filter(|mov| is_not_pinned(threats, mov))
         .collect()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::{chess_move::MoveType, epd::parse_epd_suite},
+        engine::sender::convert_move_to_uci,
+    };
+
+    fn get_quiet_checks_as_uci(board: &Chessboard) -> Vec<String> {
+        let mut moves = SmallVec::new();
+        get_quiet_checks(board, &get_threats(board), &mut moves);
+        let mut uci_moves: Vec<String> = moves.into_iter().map(convert_move_to_uci).collect();
+        uci_moves.sort();
+        uci_moves
+    }
+
+    // every legal silent move (without castles) which leaves the opponent in check
+    fn check_same_as_made_moves(board: &Chessboard, depth: u8) {
+        let (moves, is_in_check) = get_valid_moves_in_position(board, true);
+        if !is_in_check {
+            let mut expected: Vec<String> = moves
+                .iter()
+                .filter(|mov| !mov.is_capture() && !mov.is_promotion() && !mov.is_castle())
+                .filter(|mov| {
+                    let mut new_board = *board;
+                    new_board.move_figure(**mov);
+                    get_threats(&new_board).is_in_check
+                })
+                .map(|mov| convert_move_to_uci(*mov))
+                .collect();
+            expected.sort();
+            assert_eq!(expected, get_quiet_checks_as_uci(board));
+        }
+        if depth == 0 {
+            return;
+        }
+        for single in moves {
+            let mut new_board = *board;
+            new_board.move_figure(single);
+            check_same_as_made_moves(&new_board, depth - 1);
+        }
+    }
+
+    #[test]
+    fn test_quiet_checks_match_made_moves() {
+        for epd in parse_epd_suite(include_str!("../../suites/perft.epd")) {
+            check_same_as_made_moves(&epd.create_board(), 2);
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_of_other_positions() {
+        let board = Chessboard::from_fen("4k3/8/8/8/8/8/3q4/R3K3 w - - 0 1");
+        let threats = get_threats(&board);
+        // no move, no own piece on the field and a wrong move type
+        assert!(!is_legal(&board, &threats, Move::default()));
+        assert!(!is_legal(&board, &threats, Move::new(8, 16, MoveType::Quiet)));
+        assert!(!is_legal(&board, &threats, Move::new(4, 11, MoveType::Quiet)));
+        // in check only evasions are legal
+        assert!(!is_legal(&board, &threats, Move::new(0, 8, MoveType::Quiet)));
+        assert!(is_legal(&board, &threats, Move::new(4, 11, MoveType::Capture)));
+
+        // the pinned rook can only move on the line to the pinner
+        let board = Chessboard::from_fen("4r1k1/8/8/8/8/8/4R3/4K3 w - - 0 1");
+        let threats = get_threats(&board);
+        assert!(is_pseudo_legal(&board, &threats, Move::new(12, 8, MoveType::Quiet)));
+        assert!(!is_legal(&board, &threats, Move::new(12, 8, MoveType::Quiet)));
+        assert!(is_legal(&board, &threats, Move::new(12, 60, MoveType::Capture)));
+    }
+
+    #[test]
+    fn test_direct_and_discovered_checks() {
+        // direct checks of every piece type
+        assert_eq!(vec!["a1a8"], get_quiet_checks_as_uci(&Chessboard::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1")));
+        assert_eq!(vec!["e4d6", "e4f6"], get_quiet_checks_as_uci(&Chessboard::from_fen("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1")));
+        assert_eq!(vec!["d6d7"], get_quiet_checks_as_uci(&Chessboard::from_fen("4k3/8/3P4/8/8/8/8/4K3 w - - 0 1")));
+        // bishop in front of the rook - each of its 12 moves uncovers the rook
+        let checks = get_quiet_checks_as_uci(&Chessboard::from_fen("4k3/8/8/8/4B3/8/8/1N2R1K1 w - - 0 1"));
+        assert_eq!(12, checks.len());
+        assert!(checks.iter().all(|mov| mov.starts_with("e4")));
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::board::{
+    board::Chessboard,
+    chess_move::Move,
+    epd::{read_epd_file, Epd},
+};
+
+use super::{moves::get_valid_moves_in_position, sender::convert_move_to_uci};
+
+// perft <depth> [hash <mb>] [fen <fen> | epd <file>] - without fen/epd the current position is used
+pub fn run_perft(commands: &[&str], board: &Chessboard) {
+    let Some(depth) = commands.get(1).and_then(|x| x.parse::<u8>().ok()) else {
+        println!("usage: perft <depth> [hash <mb>] [fen <fen> | epd <file>]");
+        return;
+    };
+    let table = get_option(commands, "hash").map(PerftTable::new);
+
+    if let Some(file) = commands.iter().position(|x| *x == "epd").and_then(|index| commands.get(index + 1)) {
+        match read_epd_file(file) {
+            Ok(suite) => {
+                run_perft_suite(&suite, depth, table.as_ref());
+            }
+            Err(error) => println!("could not read {}: {}", file, error),
+        }
+        return;
+    }
+
+    let mut position = *board;
+    if let Some(index) = commands.iter().position(|x| *x == "fen") {
+        let fen: Vec<&str> = commands.iter().skip(index + 1).take_while(|x| **x != "hash").copied().collect();
+        position.create_position_from_input_string(fen.join(" "));
+    }
+    run_divide(&position, depth, table.as_ref());
+}
+
+fn get_option(commands: &[&str], key: &str) -> Option<usize> {
+    let index = commands.iter().position(|x| *x == key)?;
+    commands.get(index + 1)?.parse().ok()
+}
+
+fn run_divide(board: &Chessboard, depth: u8, table: Option<&PerftTable>) {
+    let now = SystemTime::now();
+    let mut divided = divide(board, depth, table);
+    divided.sort_by_key(|(mov, _)| convert_move_to_uci(*mov));
+    for (mov, nodes) in divided.iter() {
+        println!("{}: {}", convert_move_to_uci(*mov), nodes);
+    }
+    let nodes: u64 = divided.iter().map(|(_, nodes)| nodes).sum();
+    println!();
+    println!("Nodes searched: {}", nodes);
+    print_speed(nodes, now);
+}
+
+// prints every depth of every position and returns the number of mismatches
+fn run_perft_suite(suite: &[Epd], max_depth: u8, table: Option<&PerftTable>) -> usize {
+    let now = SystemTime::now();
+    let mut total_nodes = 0;
+    let mut mismatches = 0;
+    for epd in suite {
+        let board = epd.create_board();
+        let name = epd.id.clone().unwrap_or(epd.position.clone());
+        let depth = max_depth.min(epd.max_perft_depth().unwrap_or(0));
+        for current_depth in 1..=depth {
+            let Some(expected) = epd.perft[current_depth as usize - 1] else {
+                continue;
+            };
+            let nodes = perft_parallel(&board, current_depth, table);
+            total_nodes += nodes;
+            if nodes == expected {
+                println!("{} D{}: {} ok", name, current_depth, nodes);
+            } else {
+                mismatches += 1;
+                println!("{} D{}: {} expected {} - MISMATCH", name, current_depth, nodes, expected);
+            }
+        }
+    }
+    println!();
+    println!("{} positions - {} mismatches", suite.len(), mismatches);
+    print_speed(total_nodes, now);
+    mismatches
+}
+
+fn print_speed(nodes: u64, start: SystemTime) {
+    let elapsed = start.elapsed().unwrap_or_default();
+    let nodes_per_second = (nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+    println!("took: {:?} - {} nodes/s", elapsed, nodes_per_second);
+}
+
+// count all leaf nodes at the given depth - used to check if the move generation is still working
+pub fn perft(board: &Chessboard, depth: u8, table: Option<&PerftTable>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let (valid_moves, _) = get_valid_moves_in_position(board, true);
+    // bulk counting - the moves of the last ply don't need to be made
+    if depth == 1 {
+        return valid_moves.len() as u64;
+    }
+    if let Some(nodes) = table.and_then(|table| table.get(board.zobrist_key, depth)) {
+        return nodes;
+    }
+
+    let mut nodes = 0;
+    for single in valid_moves {
+        let mut new_board = *board;
+        new_board.move_figure(single);
+        nodes += perft(&new_board, depth - 1, table);
+    }
+    if let Some(table) = table {
+        table.store(board.zobrist_key, depth, nodes);
+    }
+    nodes
+}
+
+// same as perft - but the moves at the root are calculated in parallel
+pub fn perft_parallel(board: &Chessboard, depth: u8, table: Option<&PerftTable>) -> u64 {
+    if depth <= 1 {
+        return perft(board, depth, table);
+    }
+    divide(board, depth, table).iter().map(|(_, nodes)| nodes).sum()
+}
+
+// nodes after each root move
+pub fn divide(board: &Chessboard, depth: u8, table: Option<&PerftTable>) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    let (valid_moves, _) = get_valid_moves_in_position(board, true);
+    valid_moves
+        .par_iter()
+        .map(|single| {
+            let mut new_board = *board;
+            new_board.move_figure(*single);
+            (*single, perft(&new_board, depth - 1, table))
+        })
+        .collect()
+}
+
+// always replacing hash table shared by all threads
+// key is stored xored with the data, so entries written by two threads at once are not used
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+#[derive(Default)]
+struct PerftEntry {
+    key: AtomicU64,
+    // nodes << 8 | depth
+    data: AtomicU64,
+}
+
+impl PerftTable {
+    pub fn new(size_in_mb: usize) -> PerftTable {
+        let wanted_entries = (size_in_mb.max(1) << 20) / std::mem::size_of::<PerftEntry>();
+        // power of two to get the index by masking the key
+        let count = 1 << wanted_entries.ilog2();
+        PerftTable {
+            entries: (0..count).map(|_| PerftEntry::default()).collect(),
+        }
+    }
+
+    fn get(&self, key: u64, depth: u8) -> Option<u64> {
+        let entry = &self.entries[key as usize & (self.entries.len() - 1)];
+        let data = entry.data.load(Ordering::Relaxed);
+        if entry.key.load(Ordering::Relaxed) ^ data != key || data as u8 != depth {
+            return None;
+        }
+        Some(data >> 8)
+    }
+
+    fn store(&self, key: u64, depth: u8, nodes: u64) {
+        let entry = &self.entries[key as usize & (self.entries.len() - 1)];
+        let data = nodes << 8 | depth as u64;
+        entry.key.store(key ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::epd::parse_epd_suite;
+
+    #[test]
+    fn test_divide_matches_perft() {
+        let board = Chessboard {
+            ..Default::default()
+        };
+        let divided = divide(&board, 3, None);
+        assert_eq!(20, divided.len());
+        assert_eq!(8902, divided.iter().map(|(_, nodes)| nodes).sum::<u64>());
+        let e2e4 = divided.iter().find(|(mov, _)| convert_move_to_uci(*mov) == "e2e4").unwrap();
+        assert_eq!(600, e2e4.1);
+        assert_eq!(1, perft(&board, 0, None));
+    }
+
+    #[test]
+    fn test_hash_table_gives_same_results() {
+        let table = PerftTable::new(1);
+        for epd in parse_epd_suite(include_str!("../../suites/perft.epd")) {
+            let board = epd.create_board();
+            for depth in 1..=3 {
+                if let Some(expected) = epd.perft[depth - 1] {
+                    assert_eq!(expected, perft_parallel(&board, depth as u8, Some(&table)), "{}", epd.position);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_suite_reports_mismatches() {
+        let mut suite = parse_epd_suite(include_str!("../../suites/perft.epd"));
+        assert_eq!(0, run_perft_suite(&suite, 2, None));
+        suite[0].perft[1] = Some(401);
+        assert_eq!(1, run_perft_suite(&suite, 2, None));
+    }
+}
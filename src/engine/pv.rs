@@ -0,0 +1,77 @@
+use crate::board::chess_move::Move;
+
+// deeper lines are not collected (the search could in theory go deeper than this)
+pub const MAX_PLY: usize = 128;
+
+// triangular pv table - row ply holds the best line found from this ply on
+// a node copies the line of its child behind its own best move
+pub struct PvTable {
+    moves: [[Move; MAX_PLY]; MAX_PLY],
+    length: [usize; MAX_PLY],
+}
+
+impl Default for PvTable {
+    fn default() -> PvTable {
+        PvTable {
+            moves: [[Move::default(); MAX_PLY]; MAX_PLY],
+            length: [0; MAX_PLY],
+        }
+    }
+}
+
+impl PvTable {
+    // has to be called when a node is entered - lines of previous nodes on this ply are stale
+    pub fn clear(&mut self, ply: usize) {
+        if ply < MAX_PLY {
+            self.length[ply] = ply;
+        }
+    }
+
+    // new best move of the node - the line of the child (cleared or searched) follows it
+    pub fn update(&mut self, ply: usize, mov: Move) {
+        if ply >= MAX_PLY {
+            return;
+        }
+        let child_length = if ply + 1 < MAX_PLY { self.length[ply + 1] } else { ply + 1 };
+        self.moves[ply][ply] = mov;
+        for next in ply + 1..child_length {
+            self.moves[ply][next] = self.moves[ply + 1][next];
+        }
+        self.length[ply] = child_length.max(ply + 1);
+    }
+
+    pub fn get_line(&self, ply: usize) -> &[Move] {
+        if ply >= MAX_PLY {
+            return &[];
+        }
+        &self.moves[ply][ply..self.length[ply]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::chess_move::MoveType;
+
+    use super::*;
+
+    #[test]
+    fn test_lines_are_copied_from_the_child() {
+        let first = Move::new(12, 28, MoveType::DoublePawnPush);
+        let second = Move::new(52, 36, MoveType::DoublePawnPush);
+        let third = Move::new(6, 21, MoveType::Quiet);
+        let mut pv = PvTable::default();
+        pv.clear(0);
+        pv.clear(1);
+        pv.clear(2);
+        pv.update(2, third);
+        pv.update(1, second);
+        pv.update(0, first);
+        assert_eq!(&[first, second, third], pv.get_line(0));
+
+        // a better move whose child line is empty replaces the whole line
+        pv.clear(1);
+        pv.update(0, second);
+        assert_eq!(&[second], pv.get_line(0));
+        assert!(pv.get_line(MAX_PLY).is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    board::{
+        bitboard::Bitboard,
+        board::Chessboard,
+        chess_move::{Move, MoveType},
+        promotion::convert_promotion_to_figure,
+        square::{Rank, Square},
+    },
+    figures::{
+        color::Color,
+        piece::{Piece, ALL_PIECES, PIECE_VALUES},
+    },
+};
+
+use super::attacks::{attackers_to, slider_attackers_to};
+
+// static exchange evaluation - material we win (or lose) in centipawns if both sides
+// keep capturing on the target field with their least valuable piece (pins are ignored)
+pub fn see(board: &Chessboard, mov: Move) -> i32 {
+    if mov.is_castle() {
+        return 0;
+    }
+    let (from, to) = (mov.from(), mov.to());
+    let mut gain = [0; 32];
+    let mut occupied = board.positions;
+    occupied.remove_field(from);
+
+    let mut on_field = board
+        .get_type_of_figure(board.current_move, from)
+        .map_or(0, |piece| PIECE_VALUES[piece as usize]);
+    gain[0] = match mov.move_type() {
+        MoveType::EnPassant => {
+            // captured pawn is not standing on the target field
+            if let Some(en_passant) = board.en_passant {
+                occupied.remove_field(en_passant);
+            }
+            PIECE_VALUES[Piece::Pawn as usize]
+        }
+        _ => board
+            .get_type_of_figure(board.get_opponent_color(), to)
+            .map_or(0, |piece| PIECE_VALUES[piece as usize]),
+    };
+    if let Some(promoted_to) = mov.promoted_to() {
+        let promoted_value = PIECE_VALUES[convert_promotion_to_figure(promoted_to) as usize];
+        gain[0] += promoted_value - PIECE_VALUES[Piece::Pawn as usize];
+        on_field = promoted_value;
+    }
+
+    let mut attackers = attackers_to(board, to, occupied) & occupied;
+    let mut color = board.get_opponent_color();
+    let mut depth = 0;
+    loop {
+        let own_attackers = attackers & board.used_positions[color as usize];
+        let Some((piece, position)) = get_least_valuable_attacker(board, color, own_attackers) else {
+            break;
+        };
+        // the king can not capture a defended piece
+        if piece == Piece::King && !(attackers & board.used_positions[get_opponent(color) as usize]).is_empty() {
+            break;
+        }
+        depth += 1;
+        gain[depth] = on_field - gain[depth - 1];
+        on_field = PIECE_VALUES[piece as usize];
+        if piece == Piece::Pawn && [Rank::FIRST, Rank::EIGHTH].contains(&Square::from_index(to).rank()) {
+            gain[depth] += PIECE_VALUES[Piece::Queen as usize] - PIECE_VALUES[Piece::Pawn as usize];
+            on_field = PIECE_VALUES[Piece::Queen as usize];
+        }
+        // no matter how the exchange goes on - it can not change the result
+        if (-gain[depth - 1]).max(gain[depth]) < 0 {
+            break;
+        }
+        occupied.remove_field(position);
+        // x-rays - sliders behind the capturing piece are attacking now
+        attackers = (attackers | slider_attackers_to(board, to, occupied)) & occupied;
+        color = get_opponent(color);
+    }
+    // every side can stop capturing if it would lose material
+    while depth > 0 {
+        gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        depth -= 1;
+    }
+    gain[0]
+}
+
+// true if the exchange wins at least the threshold - used for pruning
+pub fn see_ge(board: &Chessboard, mov: Move, threshold: i32) -> bool {
+    see(board, mov) >= threshold
+}
+
+fn get_opponent(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+fn get_least_valuable_attacker(board: &Chessboard, color: Color, attackers: Bitboard) -> Option<(Piece, usize)> {
+    ALL_PIECES.into_iter().find_map(|piece| {
+        let pieces = attackers & *board.get_pieces(color, piece);
+        (!pieces.is_empty()).then(|| (piece, pieces.get_first_field()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn see_of(fen: &str, uci_move: &str) -> i32 {
+        let board = Chessboard::from_fen(fen);
+        see(&board, board.get_move_from_uci_input(uci_move).unwrap())
+    }
+
+    #[test]
+    fn test_simple_exchanges() {
+        // undefended pawn
+        assert_eq!(100, see_of("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"));
+        // pawn defended by a pawn - rook is lost
+        assert_eq!(-400, see_of("1k6/8/5p2/4p3/8/8/8/1K2R3 w - - 0 1", "e1e5"));
+        // knight defended by a pawn - knight for knight
+        assert_eq!(0, see_of("1k6/8/5p2/4n3/8/3N4/8/1K6 w - - 0 1", "d3e5"));
+        // quiet moves to an attacked and a safe field
+        assert_eq!(-300, see_of("1k6/8/8/8/2p5/8/8/1K2N3 w - - 0 1", "e1d3"));
+        assert_eq!(0, see_of("1k6/8/8/8/8/8/8/1K2N3 w - - 0 1", "e1d3"));
+    }
+
+    #[test]
+    fn test_x_rays() {
+        // rook behind the rook recaptures after the first exchange
+        assert_eq!(100, see_of("1k2r3/8/8/4p3/8/8/4R3/1K2R3 w - - 0 1", "e2e5"));
+        // without the second rook the pawn is defended
+        assert_eq!(-400, see_of("1k2r3/8/8/4p3/8/8/4R3/1K6 w - - 0 1", "e2e5"));
+        // queen behind the bishop takes back - knight and pawn for the bishop
+        assert_eq!(80, see_of("1k6/6p1/5n2/8/8/2B5/1Q6/1K6 w - - 0 1", "c3f6"));
+        // taking back with the queen would lose it to the rook - we stop after bishop for knight
+        assert_eq!(-20, see_of("1k1r4/8/4b3/3n4/8/1B6/Q7/1K6 w - - 0 1", "b3d5"));
+    }
+
+    #[test]
+    fn test_king_does_not_capture_defended_pieces() {
+        // only the king defends the pawn
+        assert_eq!(-400, see_of("8/8/8/3k4/4p3/8/8/2K1R3 w - - 0 1", "e1e4"));
+        // the bishop defends the rook - the king can not take back
+        assert_eq!(100, see_of("8/8/8/3k4/4p3/8/8/1BK1R3 w - - 0 1", "e1e4"));
+    }
+
+    #[test]
+    fn test_en_passant_and_promotion() {
+        // en passant against a pawn defended by a pawn
+        assert_eq!(0, see_of("1k6/2p5/8/3pP3/8/8/8/1K6 w - d6 0 1", "e5d6"));
+        // promotion on a defended field - the queen is lost
+        assert_eq!(-100, see_of("1k5r/6P1/8/8/8/8/8/1K6 w - - 0 1", "g7g8q"));
+        // promotion with capture of an undefended rook
+        assert_eq!(1300, see_of("1k5r/6P1/8/8/8/8/8/1K6 w - - 0 1", "g7h8q"));
+    }
+
+    #[test]
+    fn test_see_ge() {
+        let board = Chessboard::from_fen("1k6/8/5p2/4n3/8/3N4/8/1K6 w - - 0 1");
+        let mov = board.get_move_from_uci_input("d3e5").unwrap();
+        assert!(see_ge(&board, mov, 0));
+        assert!(!see_ge(&board, mov, 1));
+        assert!(see_ge(&board, mov, -300));
+    }
+}
@@ -1,41 +1,37 @@
 use log::info;
 
-use crate::board::promotion::{convert_promotion_to_output_string, Promotion};
-
-fn get_row_from_number(row: usize) -> String {
-    match row % 8 {
-        0 => String::from("a"),
-        1 => String::from("b"),
-        2 => String::from("c"),
-        3 => String::from("d"),
-        4 => String::from("e"),
-        5 => String::from("f"),
-        6 => String::from("g"),
-        _ => String::from("h"),
-    }
-}
+use crate::board::{
+    chess_move::Move, promotion::convert_promotion_to_output_string, square::Square,
+};
 
-fn convert_number_to_chess_notation(position: usize) -> String {
-    let mut row = get_row_from_number(position).to_owned();
-    let column = position / 8 + 1;
+pub fn convert_number_to_chess_notation(position: usize) -> String {
+    Square::from_index(position).to_string()
+}
 
-    row.push_str(&column.to_string());
-    row
+pub fn convert_move_to_uci(mov: Move) -> String {
+    let mut uci = convert_number_to_chess_notation(mov.from());
+    uci.push_str(&convert_number_to_chess_notation(mov.to()));
+    // uci expects lowercase promotions (e7e8q)
+    uci.push_str(&convert_promotion_to_output_string(mov.promoted_to()).to_lowercase());
+    uci
 }
 
-pub fn send_move(old_position: usize, new_position: usize, promoted_to: Option<Promotion>) {
-    let old_field = convert_number_to_chess_notation(old_position);
-    let new_field = convert_number_to_chess_notation(new_position);
-    let promoted_to = convert_promotion_to_output_string(promoted_to);
+// ponder is the answer of the opponent we expect (second move of the pv)
+pub fn send_move(mov: Move, ponder: Option<Move>) {
+    let uci_move = convert_move_to_uci(mov);
 
-    info!("Found best Move was: {}{}{}", old_field, new_field, promoted_to);
-    println!("bestmove {}{}{}", old_field, new_field, promoted_to);
+    info!("Found best Move was: {}", uci_move);
+    match ponder {
+        Some(ponder) => println!("bestmove {} ponder {}", uci_move, convert_move_to_uci(ponder)),
+        None => println!("bestmove {}", uci_move),
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::board::board::Chessboard;
 
     #[test]
     fn test_move_convert() {
@@ -48,4 +44,14 @@ mod tests {
         assert_eq!("c8", convert_number_to_chess_notation(58));
         assert_eq!("d4", convert_number_to_chess_notation(27));
     }
+
+    #[test]
+    fn test_promotion_round_trip() {
+        let board = Chessboard::from_fen("7k/4P3/8/8/8/8/8/4K3 w - - 0 1");
+        for uci in ["e7e8q", "e7e8r", "e7e8b", "e7e8n"] {
+            let mov = board.get_move_from_uci_input(uci).unwrap();
+            assert!(mov.is_promotion(), "{}", uci);
+            assert_eq!(uci, convert_move_to_uci(mov));
+        }
+    }
 }
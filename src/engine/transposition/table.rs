@@ -1,10 +1,12 @@
-use crate::TRANSPOSITION_TABLE;
+use dashmap::DashMap;
 
 use super::transposition::{Flag, Transposition};
 
+// shared by all threads of a search - the engine uses the global table, tests their own
+pub type TranspositionTable = DashMap<u64, Transposition>;
 
-pub fn get_entry_without_check(board_hash: u64) -> Option<Transposition> {
-    if let Some(transposition) = TRANSPOSITION_TABLE.get(&board_hash) {
+pub fn get_entry_without_check(table: &TranspositionTable, board_hash: u64) -> Option<Transposition> {
+    if let Some(transposition) = table.get(&board_hash) {
         if transposition.hash == board_hash {
             return Some(*transposition);
         }
@@ -12,8 +14,8 @@ pub fn get_entry_without_check(board_hash: u64) -> Option<Transposition> {
     None
 }
 
-pub fn get_entry(board_hash: u64, depth: u8, alpha: f32, beta: f32) -> Option<Transposition> {
-    if let Some(transposition) = TRANSPOSITION_TABLE.get(&board_hash) {
+pub fn get_entry(table: &TranspositionTable, board_hash: u64, depth: u8, alpha: f32, beta: f32) -> Option<Transposition> {
+    if let Some(transposition) = table.get(&board_hash) {
         // field is currently used by default value or we need to search deeper
         if transposition.hash != board_hash || transposition.depth < depth {
             return None;
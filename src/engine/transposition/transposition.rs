@@ -1,7 +1,8 @@
-use crate::{engine::engine::PossibleMove};
+use crate::board::chess_move::Move;
 
-#[derive(Clone, Debug, Copy, PartialEq)]
+#[derive(Clone, Debug, Copy, PartialEq, Default)]
 pub enum Flag {
+    #[default]
     Exact,
     Lowerbound,
     Upperbound
@@ -13,7 +14,7 @@ pub struct Transposition{
     pub hash: u64,
     pub depth: u8,
     pub evaluation: f32,
-    pub best_move: PossibleMove,
+    pub best_move: Move,
     pub flag: Flag
 }
 
@@ -23,7 +24,7 @@ impl Default for Transposition {
             hash: 0,
             depth: 0,
             evaluation: 0.0,
-            best_move: PossibleMove{from: 0, to: 0, promoted_to: None},
+            best_move: Move::default(),
             flag: Flag::Exact
         }
     }
@@ -1,6 +1,6 @@
 use std::{u64, usize};
 
-use crate::{board::{bitboard::Bitboard, board::Chessboard}, figures::{color::Color, piece::Piece, sliding_moves::{get_fields_threatened_by_bishop, get_fields_threatened_by_queen, get_fields_threatened_by_rook}}, DOUPLICATE_PAWN_TARIFF, KING_SAFETY_FIELDS, PASSED_PAWN_ROWS};
+use crate::{board::{bitboard::Bitboard, board::Chessboard, square::{File, Rank, Square}}, figures::{color::Color, piece::Piece, sliding_moves::{get_fields_threatened_by_bishop, get_fields_threatened_by_queen, get_fields_threatened_by_rook}}, DOUPLICATE_PAWN_TARIFF, KING_SAFETY_FIELDS, PASSED_PAWN_ROWS};
 
 // a1 to h8
 const PAWN_RATE_KING_CENTER: [f32; 64] = [
@@ -80,8 +80,8 @@ const QUEEN_RATE: [f32; 64] = [
     8.7, 8.8, 9.0, 9.0, 9.0, 9.0, 8.8, 8.7,
 ];
 
-// white and black is different rate due to king castle (and starting on other square)
-const EARLY_GAME_KING_RATE_WHITE: [f32; 64] = [
+// from white pov - black uses the mirrored field
+const EARLY_GAME_KING_RATE: [f32; 64] = [
     1.2, 1.3, 1.3, 1.0, 1.0, 1.1, 1.3, 1.2,
     0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 
     0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 
@@ -92,18 +92,6 @@ const EARLY_GAME_KING_RATE_WHITE: [f32; 64] = [
     0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 
 ];
 
-// from back pov (so reverse)
-const EARLY_GAME_KING_RATE_BLACK: [f32; 64] = [
-    0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 
-    0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 
-    0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 
-    0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 
-    0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 
-    0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3,
-    0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5,  
-    1.2, 1.3, 1.3, 1.0, 1.0, 1.1, 1.3, 1.2,
-];
-
 const LATE_GAME_KING_RATE: [f32; 64] = [
     1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 
     1.0, 1.1, 1.1, 1.1, 1.1, 1.1, 1.1, 1.0, 
@@ -123,15 +111,9 @@ pub enum KingPosition{
 }
 
 
-fn check_where_king_is_located(king_position: usize, is_black_king: bool) -> KingPosition{
-    if is_black_king{
-        return match king_position % 8{
-            0..=2 => KingPosition::RIGHT,
-            5..=7 => KingPosition::LEFT,
-            _ => KingPosition::CENTER
-        }
-    }                                    
-    match king_position % 8{
+// files are the same for both colors - only the ranks are mirrored
+fn check_where_king_is_located(king_position: usize) -> KingPosition{
+    match Square::from_index(king_position).file().index(){
         0..=2 => KingPosition::LEFT,
         5..=7 => KingPosition::RIGHT,
         _ => KingPosition::CENTER
@@ -147,10 +129,7 @@ fn get_king_weight(position: usize, king_position: KingPosition, color: Color, o
     let fields = KING_SAFETY_FIELDS[color as usize][king_position as usize].board & own_positions.board;
 
     let safety_bonus = fields.count_ones() as f32 * 0.05;
-    match color{
-        Color::White => safety_bonus + EARLY_GAME_KING_RATE_WHITE[position],
-        Color::Black => safety_bonus + EARLY_GAME_KING_RATE_BLACK[position]
-    }
+    safety_bonus + EARLY_GAME_KING_RATE[position]
 }
 
 fn get_pawn_rate(position: usize, king_position: &KingPosition) -> f32{
@@ -162,9 +141,10 @@ fn get_pawn_rate(position: usize, king_position: &KingPosition) -> f32{
     }
 }
 
-fn get_rook_weight(position: usize, color: Color, board: &Chessboard, king_position: usize) -> f32{
+// position is the real field, relative_position the field from the pov of the color
+fn get_rook_weight(position: usize, relative_position: usize, color: Color, board: &Chessboard, king_position: usize) -> f32{
     let threatened_fields = get_fields_threatened_by_rook(board, position, king_position);
-    let mut threat_bonus = ROOK_RATE[position];
+    let mut threat_bonus = ROOK_RATE[relative_position];
     threat_bonus += 0.02 * threatened_fields.board.count_ones() as f32;
 
     // add extra bonus if rook can capture our other rook (connecting rooks always a good idea)
@@ -174,57 +154,67 @@ fn get_rook_weight(position: usize, color: Color, board: &Chessboard, king_posit
     threat_bonus
 }
 
-fn get_bishop_weight( position: usize, board: &Chessboard, king_position: usize) -> f32{
+fn get_bishop_weight(position: usize, relative_position: usize, board: &Chessboard, king_position: usize) -> f32{
     let threatened_fields = get_fields_threatened_by_bishop(board, position, king_position);
-    let mut weight = BISHOP_RATE[position];
+    let mut weight = BISHOP_RATE[relative_position];
     weight += 0.02*threatened_fields.board.count_ones() as f32;
     weight
 }
 
-fn get_queen_weight(position: usize, board: &Chessboard, king_position: usize) -> f32{
+fn get_queen_weight(position: usize, relative_position: usize, board: &Chessboard, king_position: usize) -> f32{
     let threatened_fields = get_fields_threatened_by_queen(board, position, king_position);
-    let mut weight = QUEEN_RATE[position];
+    let mut weight = QUEEN_RATE[relative_position];
     weight += 0.005 * threatened_fields.board.count_ones() as f32;
     weight
 }
 
 fn get_position_weight(board: &Chessboard, color: Color, king_position: KingPosition, pieces: f32, king_usize: usize) -> f32 {
     let mut score: f32 = 0.0;
-    board.get_pieces(color, Piece::Pawn).iterate_board(|position| 
-        score+=get_pawn_rate(get_position(position, color), &king_position));
+    get_relative_pieces(board, color, Piece::Pawn).iterate_board(|relative|
+        score+=get_pawn_rate(relative, &king_position));
 
-    board.get_pieces(color, Piece::Knight).iterate_board(|position| 
-        score += KNIGHT_RATE[get_position(position, color)]);
+    get_relative_pieces(board, color, Piece::Knight).iterate_board(|relative|
+        score += KNIGHT_RATE[relative]);
 
-    board.get_pieces(color, Piece::Bishop).iterate_board(|position| 
-        score+=get_bishop_weight(get_position(position, color), board, king_usize));
+    get_relative_pieces(board, color, Piece::Bishop).iterate_board(|relative|
+        score+=get_bishop_weight(get_position(relative, color), relative, board, king_usize));
 
-    board.get_pieces(color, Piece::Rook).iterate_board(|position| 
-        score+=get_rook_weight(get_position(position, color), color, board, king_usize));
+    get_relative_pieces(board, color, Piece::Rook).iterate_board(|relative|
+        score+=get_rook_weight(get_position(relative, color), relative, color, board, king_usize));
 
-    board.get_pieces(color, Piece::Queen).iterate_board(|position| 
-        score+=get_queen_weight(get_position(position, color), board, king_usize));
+    get_relative_pieces(board, color, Piece::Queen).iterate_board(|relative|
+        score+=get_queen_weight(get_position(relative, color), relative, board, king_usize));
 
-    score+=get_king_weight(king_usize, king_position, color,board.get_positions_by_current_player(), pieces);
+    score+=get_king_weight(get_position(king_usize, color), king_position, color, &board.used_positions[color as usize], pieces);
     score
 }
 
+// field from the pov of the color (a1 of black is a8) - also converts it back
 fn get_position(position: usize, color: Color) -> usize{
+    Square::from_index(position).relative_to(color).index()
+}
+
+// pieces from the pov of the color - both colors are iterated in the same order
+// so the evaluation of a mirrored position is exactly the negated evaluation
+fn get_relative_pieces(board: &Chessboard, color: Color, piece: Piece) -> Bitboard{
+    let pieces = *board.get_pieces(color, piece);
     match color{
-            Color::White => position,
-            Color::Black => 63 - position    
-        }
+        Color::White => pieces,
+        Color::Black => pieces.mirror()
+    }
 }
 
 fn get_opponent_king_bonus(piece_value: f32, king_position: &usize) -> f32{
     if piece_value > 15.0{
         return 0.0
     }
-    let king_column = king_position % 8 ;
-    if !(&8..=&55).contains(&king_position) || king_column == 0 || king_column == 7{
+    let king_square = Square::from_index(*king_position);
+    let (king_file, king_rank) = (king_square.file(), king_square.rank());
+    // distance to the edge of the board
+    if [File::A, File::H].contains(&king_file) || [Rank::FIRST, Rank::EIGHTH].contains(&king_rank){
         return 2.0;
     };
-    if !(&15..=&47).contains(&king_position) || king_column == 1 || king_column == 6{
+    if [File::B, File::G].contains(&king_file) || [Rank::SECOND, Rank::SEVENTH].contains(&king_rank){
         return 1.0;
     }
     0.0
@@ -270,8 +260,8 @@ fn evaluate(board: &Chessboard) -> f32 {
     let white_king_usize =  board.get_pieces(Color::White, Piece::King).get_first_field(); 
     let black_king_usize = board.get_pieces(Color::Black, Piece::King).get_first_field();
 
-    let white_king_position = check_where_king_is_located(white_king_usize, false);
-    let black_king_position = check_where_king_is_located(black_king_usize, true);
+    let white_king_position = check_where_king_is_located(white_king_usize);
+    let black_king_position = check_where_king_is_located(black_king_usize);
 
     let white_pieces_position_value = get_position_weight(board, Color::White, white_king_position, black_pieces_value, white_king_usize);
     let black_pieces_position_value = get_position_weight(board, Color::Black, black_king_position, white_pieces_value, black_king_usize);
@@ -284,8 +274,8 @@ fn evaluate(board: &Chessboard) -> f32 {
     let black_douplicate_pawn_tariff = get_douplicate_pawn_tariff(board, Color::Black);   
 
     // give extra bonus to passed pawns
-    let white_passed_pawn_value = get_passed_pawn_bonus_white(board);
-    let black_passed_pawn_value = get_passed_pawn_bonus_black(board);
+    let white_passed_pawn_value = get_passed_pawn_bonus(board, Color::White);
+    let black_passed_pawn_value = get_passed_pawn_bonus(board, Color::Black);
 
     let white_value = white_pieces_value 
                             + white_pieces_position_value 
@@ -302,17 +292,21 @@ fn evaluate(board: &Chessboard) -> f32 {
     white_value - black_value
 }
 
-fn get_passed_pawn_bonus_white(board: &Chessboard) -> f32{
+fn get_passed_pawn_bonus(board: &Chessboard, color: Color) -> f32{
     let mut bonus = 0.0;
 
-    let white_pawns = board.get_pieces(Color::White, Piece::Pawn);
-    white_pawns.iterate_board(|pawn_position| {
+    // both colors are handled from the pov of white
+    let opponent_pawns = match color{
+        Color::White => *board.get_pieces(Color::Black, Piece::Pawn),
+        Color::Black => board.get_pieces(Color::White, Piece::Pawn).mirror()
+    };
+    get_relative_pieces(board, color, Piece::Pawn).iterate_board(|pawn_position| {
         let (column, row) = get_column_and_row_from_position(pawn_position);
         // get all fields where opponent pawns can prevent this pawn from beeing a passed one
-        let relevant_columns = u64::MAX << 8*column;
+        let relevant_columns = u64::MAX << (8*column);
         let relevant_rows = PASSED_PAWN_ROWS[row-1];
 
-        let blockers =  relevant_columns & relevant_rows.board & board.get_pieces(Color::Black, Piece::Pawn).board;
+        let blockers =  relevant_columns & relevant_rows.board & opponent_pawns.board;
         // no opponent pawn on these fields -> we got a passed pawn!
         if blockers == 0{
             // the higher up the pawn - the better!
@@ -323,39 +317,72 @@ fn get_passed_pawn_bonus_white(board: &Chessboard) -> f32{
     bonus
 }
 
-fn get_passed_pawn_bonus_black(board: &Chessboard) -> f32{
-    let mut bonus = 0.0;
-
-    let white_pawns = board.get_pieces(Color::Black, Piece::Pawn);
-    white_pawns.iterate_board(|pawn_position| {
-        let (column, row) = get_column_and_row_from_position(pawn_position);
-        // get all fields where opponent pawns can prevent this pawn from beeing a passed one
-        let relevant_columns = u64::MAX >> 8*(9-column);
-        let relevant_rows = PASSED_PAWN_ROWS[row-1];
-
-        let blockers =  relevant_columns & relevant_rows.board & board.get_pieces(Color::White, Piece::Pawn).board;
-        // no opponent pawn on these fields -> we got a passed pawn!
-        if blockers == 0{
-            // the higher up the pawn - the better!
-            bonus += 0.3 * (9-column) as f32
-        }
-    });
-
-    bonus
-}
-
-
 // both starting at 1 and ending at 8
 fn get_column_and_row_from_position(position: usize) -> (usize, usize){
-    let column = position / 8 +1 ;
-    let row = position % 8 +1;
-    (column, row)
+    let square = Square::from_index(position);
+    (square.rank().index() + 1, square.file().index() + 1)
 }
 
 #[cfg(test)]
-
 mod tests{
-    use crate::{board::board::Chessboard, evaluation::{get_column_and_row_from_position, get_passed_pawn_bonus_black, get_passed_pawn_bonus_white}};
+    use super::*;
+    use crate::{board::epd::parse_epd_suite, engine::moves::get_valid_moves_in_position};
+
+    // positions from the perft suite and some middle- and endgames
+    fn get_symmetry_positions() -> Vec<Chessboard> {
+        let mut positions: Vec<Chessboard> = parse_epd_suite(include_str!("../../suites/perft.epd"))
+            .iter()
+            .map(|epd| epd.create_board())
+            .collect();
+        for fen in [
+            "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N2N2/PP2BPPP/R2QKB1R w KQ - 0 9",
+            "2kr3r/ppp2ppp/2n5/2b1p3/4P1n1/2NP1N2/PPP2PPP/R1B1KB1R b KQ - 0 9",
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            "8/5pk1/6p1/2p4p/2P2P1P/6P1/5K2/8 w - - 0 40",
+            "6k1/5p2/4p1p1/3pP3/1p1P2P1/1P3K2/8/8 b - - 0 45",
+            "8/8/3k4/8/2R5/8/4K3/8 w - - 0 60",
+            "8/2P5/8/8/8/1k6/p7/5K2 b - - 0 55",
+        ] {
+            let board = Chessboard::from_fen(fen);
+            positions.push(board);
+        }
+        positions
+    }
+
+    fn check_symmetry(board: &Chessboard, depth: u8) {
+        let mirrored = board.mirrored();
+        assert_eq!(evaluate(board), -evaluate(&mirrored));
+        assert_eq!(evaluate_for_own_color(board), evaluate_for_own_color(&mirrored));
+
+        let (moves, is_in_check) = get_valid_moves_in_position(board, true);
+        let (mirrored_moves, mirrored_is_in_check) = get_valid_moves_in_position(&mirrored, true);
+        assert_eq!(moves.len(), mirrored_moves.len());
+        assert_eq!(is_in_check, mirrored_is_in_check);
+        if depth == 0 {
+            return;
+        }
+        for single in moves {
+            let mut new_board = *board;
+            new_board.move_figure(single);
+            check_symmetry(&new_board, depth - 1);
+        }
+    }
+
+    #[test]
+    fn test_mirrored_positions_are_symmetric() {
+        for board in get_symmetry_positions() {
+            check_symmetry(&board, 1);
+        }
+    }
+
+    #[test]
+    fn test_flipped_positions_have_same_moves() {
+        // castles are lost when flipping - only positions without castle rights
+        for board in get_symmetry_positions().iter().filter(|board| !board.castle.white_can_castle() && !board.castle.black_can_castle()) {
+            let flipped = board.flipped_horizontal();
+            assert_eq!(get_valid_moves_in_position(board, true).0.len(), get_valid_moves_in_position(&flipped, true).0.len());
+        }
+    }
 
     #[test]
     fn test_column_and_row_finder(){
@@ -382,51 +409,47 @@ mod tests{
 
     #[test]
     fn test_white_passed_pawn(){
-        let mut board = Chessboard{..Default::default()};
-
         // three passed pawns - all at 2nd rank
-        board.create_position_from_input_string(String::from("k7/8/8/8/8/8/1P1P1P2/K7 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_white(&board);
+        let board = Chessboard::from_fen("k7/8/8/8/8/8/1P1P1P2/K7 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::White);
         assert_eq!(1.8, (bonus * 10.0).round() / 10.0);
 
         // now two of them are blocked by a black pawn
-        board.create_position_from_input_string(String::from("k7/2p5/8/8/8/8/1P1P1P2/K7 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_white(&board);
+        let board = Chessboard::from_fen("k7/2p5/8/8/8/8/1P1P1P2/K7 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::White);
         assert_eq!(0.6, bonus);
 
         // only one is blocked and pawn on a-row should not be effected by opponent pawn on h
-        board.create_position_from_input_string(String::from("k7/3p3p/8/8/8/8/P2P1P2/K7 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_white(&board);
+        let board = Chessboard::from_fen("k7/3p3p/8/8/8/8/P2P1P2/K7 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::White);
         assert_eq!(1.2, bonus);
 
         // same with h not effected by a 
-        board.create_position_from_input_string(String::from("k7/p2p4/8/8/8/8/P2P3P/K7 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_white(&board);
+        let board = Chessboard::from_fen("k7/p2p4/8/8/8/8/P2P3P/K7 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::White);
         assert_eq!(0.6, bonus);
 
         // pawn is one field from promotion!
-        board.create_position_from_input_string(String::from("8/1p1p2pP/8/1P1P4/8/K2k4/8/8 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_white(&board);
+        let board = Chessboard::from_fen("8/1p1p2pP/8/1P1P4/8/K2k4/8/8 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::White);
         assert_eq!(2.1, (bonus * 10.0).round() / 10.0)
     }
 
     #[test]
     fn test_black_passed_pawn(){
-        let mut board = Chessboard{..Default::default()};
-
         // one passed pawn on 7th rank
-        board.create_position_from_input_string(String::from("8/1p1p2pP/8/1P1P4/8/K2k4/8/8 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_black(&board);
+        let board = Chessboard::from_fen("8/1p1p2pP/8/1P1P4/8/K2k4/8/8 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::Black);
         assert_eq!(0.6, bonus);
 
         // one passed pawn  - but on 3rd rank!
-        board.create_position_from_input_string(String::from("8/1p1p3P/8/1P1P4/8/K2k2p1/8/8 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_black(&board);
+        let board = Chessboard::from_fen("8/1p1p3P/8/1P1P4/8/K2k2p1/8/8 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::Black);
         assert_eq!(1.8, (bonus * 10.0).round() / 10.0);
 
         // pawn on A not effected by pawn on H
-        board.create_position_from_input_string(String::from("8/8/p1pp4/2PP3P/8/K2k4/8/8 w - - 0 1"));
-        let bonus = get_passed_pawn_bonus_black(&board);
+        let board = Chessboard::from_fen("8/8/p1pp4/2PP3P/8/K2k4/8/8 w - - 0 1");
+        let bonus = get_passed_pawn_bonus(&board, Color::Black);
         assert_eq!(0.9, (bonus * 10.0).round() / 10.0);
     }
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 use smallvec::SmallVec;
 
 use crate::{
-    board::{bitboard::Bitboard, board::Chessboard}, engine::engine::PossibleMove, KING_MOVES
+    board::{bitboard::Bitboard, board::Chessboard, chess_move::{add_quiet_moves_and_captures, Move, MoveType}}, KING_MOVES
 };
 
 use super::color::Color;
@@ -10,11 +10,11 @@ pub fn get_possible_king_takes(
     board: &Chessboard,
     own_position: usize,
     opponent_moves: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
     if let Some(moves_by_field) = KING_MOVES.get(own_position){
-        let takes = Bitboard{board: moves_by_field.board & !opponent_moves.board & board.get_opponents().board};
-        takes.iterate_board(|position| possible_moves.push(PossibleMove { from: own_position, to: position, promoted_to: None }));
+        let takes = *moves_by_field & !opponent_moves & *board.get_opponents();
+        takes.iterate_board(|position| possible_moves.push(Move::new(own_position, position, MoveType::Capture)));
     }
 }
 
@@ -23,14 +23,12 @@ pub fn get_all_king_moves_in_check(
     board: &Chessboard,
     own_position: usize,
     opponent_moves: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>){
+    possible_moves: &mut SmallVec<[Move; 64]>){
         if let Some(move_by_field) = KING_MOVES.get(own_position){
-            let normal_movement = Bitboard{board: 
-                move_by_field.board 
-                & !board.get_positions_by_current_player().board
-                & !opponent_moves.board
-            };
-            normal_movement.iterate_board(|pos| possible_moves.push(PossibleMove { from: own_position, to: pos, promoted_to: None }));
+            let normal_movement = *move_by_field
+                & !*board.get_positions_by_current_player()
+                & !opponent_moves;
+            add_quiet_moves_and_captures(board, own_position, normal_movement, possible_moves);
         }
     }
 
@@ -39,15 +37,11 @@ pub fn get_possible_king_moves(
     own_position: usize,
     own_color: Color,
     opponent_moves: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
     if let Some(move_by_field) = KING_MOVES.get(own_position){
-        let normal_movement = Bitboard{board: 
-            move_by_field.board 
-            & !board.positions.board
-            & !opponent_moves.board
-        };
-        normal_movement.iterate_board(|pos| possible_moves.push(PossibleMove { from: own_position, to: pos, promoted_to: None }));
+        let normal_movement = *move_by_field & !board.positions & !opponent_moves;
+        normal_movement.iterate_board(|pos| possible_moves.push(Move::new(own_position, pos, MoveType::Quiet)));
     }
 
     // castle
@@ -88,46 +82,30 @@ fn is_possible_castle(
 fn white_castle(
     board: &Chessboard,
     opponent_moves: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>,
+    possible_moves: &mut SmallVec<[Move; 64]>,
 ) {
     // short
     if board.castle.white_castle_short && is_possible_castle(board, opponent_moves, 6, 5, None) {
-        possible_moves.push(PossibleMove {
-            to: 6,
-            from: 4,
-            promoted_to: None,
-        });
+        possible_moves.push(Move::new(4, 6, MoveType::KingCastle));
     }
     // long
     if board.castle.white_castle_long && is_possible_castle(board, opponent_moves,  2, 3, Some(1)) {
-        possible_moves.push(PossibleMove {
-            to: 2,
-            from: 4,
-            promoted_to: None,
-        });
+        possible_moves.push(Move::new(4, 2, MoveType::QueenCastle));
     }
 }
 
 fn black_castle(
     board: &Chessboard,
     opponent_moves: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ) {
     // short
     if board.castle.black_castle_short && is_possible_castle(board, opponent_moves, 62, 61, None) {
-        possible_moves.push(PossibleMove {
-            to: 62,
-            from: 60,
-            promoted_to: None,
-        });
+        possible_moves.push(Move::new(60, 62, MoveType::KingCastle));
     }
     // long
     if board.castle.black_castle_long && is_possible_castle(board, opponent_moves, 58, 59, Some(57)) {
-        possible_moves.push(PossibleMove {
-            to: 58,
-            from: 60,
-            promoted_to: None,
-        });
+        possible_moves.push(Move::new(60, 58, MoveType::QueenCastle));
     }
 }
 
@@ -141,8 +119,7 @@ mod tests {
     #[test]
     fn move_empty_board() {
 
-        let mut board = Chessboard::empty(Color::White);
-        board.castle.set_has_castled(Color::Black);
+        let board = Chessboard::empty(Color::White);
 
         let mut moves = SmallVec::new();
         get_possible_king_moves(&board, 10, Color::Black,Bitboard::new(), &mut moves);
@@ -172,7 +149,7 @@ mod tests {
         let mut moves = SmallVec::new();
         get_possible_king_moves(&board, 4, Color::White,Bitboard::new(), &mut moves);
 
-        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to).collect();
+        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to()).collect();
         // can castle left and right
         assert_eq!(7, own_move_positions.len());
 
@@ -196,7 +173,7 @@ mod tests {
 
         let mut moves = SmallVec::new();
         get_possible_king_moves(&board, 4,Color::White, opponent_moves, &mut moves);
-        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to).collect();
+        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to()).collect();
         assert_eq!(6, own_move_positions.len());
         assert_eq!(true, own_move_positions.contains(&6));
         assert_eq!(false, own_move_positions.contains(&2));
@@ -218,7 +195,7 @@ mod tests {
 
         let mut moves = SmallVec::new();
         get_possible_king_moves(&board, 4,Color::White, Bitboard::new(), &mut moves);
-        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to).collect();
+        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to()).collect();
         assert_eq!(6, own_move_positions.len());
         assert_eq!(true, own_move_positions.contains(&6));
         assert_eq!(false, own_move_positions.contains(&2));
@@ -240,7 +217,7 @@ mod tests {
 
         let mut moves = SmallVec::new();
         get_possible_king_moves(&board, 4, Color::White,Bitboard::new(), &mut moves);
-        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to).collect();
+        let own_move_positions: Vec<usize> = moves.into_iter().map(|x| x.to()).collect();
 
         // castle is not possible as there are figures in the way
         assert_eq!(5, own_move_positions.len());
@@ -1,15 +1,15 @@
 use smallvec::SmallVec;
 
-use crate::{board::{bitboard::Bitboard, board::Chessboard}, engine::engine::PossibleMove, KNIGHT_MOVES};
+use crate::{board::{bitboard::Bitboard, board::Chessboard, chess_move::{add_quiet_moves_and_captures, Move, MoveType}}, KNIGHT_MOVES};
 
 pub fn get_possible_knight_moves(
     board: &Chessboard,
     own_position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
     if let Some(moves) = KNIGHT_MOVES.get(own_position) {
-        let movement = Bitboard{board: moves.board & !board.positions.board};
-        movement.iterate_board(|position| possible_moves.push(PossibleMove { from: own_position, to: position, promoted_to: None }));      
+        let movement = *moves & !board.positions;
+        movement.iterate_board(|position| possible_moves.push(Move::new(own_position, position, MoveType::Quiet)));      
     }
 }
 
@@ -21,22 +21,23 @@ pub fn get_fields_threatened_by_knight(
 }
 
 pub fn get_possible_knight_moves_to_prevent_check(
+    board: &Chessboard,
     own_position: usize,
     prevent_check_fields: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
-    let moves = Bitboard{board: KNIGHT_MOVES[own_position].board & prevent_check_fields.board};
-    moves.iterate_board(|single| possible_moves.push(PossibleMove { from:own_position, to: single, promoted_to: None }));
+    let moves = KNIGHT_MOVES[own_position] & prevent_check_fields;
+    add_quiet_moves_and_captures(board, own_position, moves, possible_moves);
 }
 
 pub fn get_possible_knight_takes(
     board: &Chessboard,
     own_position: usize,
-    possible_takes: &mut SmallVec<[PossibleMove; 64]>
+    possible_takes: &mut SmallVec<[Move; 64]>
 ){
     if let Some(moves) = KNIGHT_MOVES.get(own_position) {
-        let movement = Bitboard{board: moves.board & board.get_opponents().board};
-        movement.iterate_board(|position| possible_takes.push(PossibleMove { to: position, from: own_position, promoted_to: None }));
+        let movement = *moves & *board.get_opponents();
+        movement.iterate_board(|position| possible_takes.push(Move::new(own_position, position, MoveType::Capture)));
     }
 }
 
@@ -1,19 +1,17 @@
 use smallvec::SmallVec;
 
-use crate::board::bitboard::Bitboard;
+use crate::board::bitboard::{between, line, Bitboard};
 use crate::board::board::Chessboard;
 use crate::board::promotion::Promotion;
-use crate::engine::engine::PossibleMove;
+use crate::board::square::{File, Rank, Square};
+use crate::board::chess_move::{Move, MoveType};
 use crate::figures::color::Color;
-use crate::{MOVES_BY_FIELD, PAWN_PROMOTION_FIELDS, PAWN_THREATS};
+use crate::{PAWN_PROMOTION_FIELDS, PAWN_THREATS};
 
 use super::piece::Piece;
 
 fn can_move_two_fields(own_position: usize, own_color: Color) -> bool {
-    match own_color {
-        Color::White => (7..=15).contains(&own_position),
-        Color::Black => (48..=55).contains(&own_position),
-    }
+    Square::from_index(own_position).rank().relative_to(own_color) == Rank::SECOND
 }
 
 fn take_left_position(one_step_forward: &usize, own_color: Color) -> usize {
@@ -45,24 +43,23 @@ fn en_passant_position_right(own_position: &usize, own_color: Color) -> usize {
 }
 
 fn figure_can_move_left(field: usize, color: &Color) -> bool {
+    let file = Square::from_index(field).file();
     match color {
-        Color::White => field % 8 != 0,
-        Color::Black => field % 8 != 7,
+        Color::White => file != File::A,
+        Color::Black => file != File::H,
     }
 }
 
 fn figure_can_move_right(field: usize, color: &Color) -> bool {
+    let file = Square::from_index(field).file();
     match color {
-        Color::White => field % 8 != 7,
-        Color::Black => field % 8 != 0,
+        Color::White => file != File::H,
+        Color::Black => file != File::A,
     }
 }
 
 fn figure_will_promote(field: usize, color: &Color) -> bool {
-    match color {
-        Color::White => field > 55,
-        Color::Black => field < 8,
-    }
+    Square::from_index(field).rank().relative_to(*color) == Rank::EIGHTH
 }
 
 fn calculate_forward_position(own_position: usize, own_color: Color, movement: usize) -> usize {
@@ -80,71 +77,49 @@ fn en_passant_no_check(
     en_passanted: &usize,
 ) -> bool {
     let own_king_board = board.get_pieces(own_color, Piece::King);
-    let own_king_position = own_king_board.get_first_field();
-
-    if let Some(moves) = MOVES_BY_FIELD.get(&own_king_position) {
-        if moves.left.contains(own_position) && moves.left.contains(en_passanted) {
-            return check_if_other_figure_in_between(
-                board,
-                &moves.left,
-                en_passanted,
-                own_position,
-            );
-        }
-        if moves.right.contains(own_position) && moves.right.contains(en_passanted) {
-            return check_if_other_figure_in_between(
-                board,
-                &moves.right,
-                en_passanted,
-                own_position,
-            );
-        }
+    if own_king_board.is_empty() {
+        return true;
     }
-    true
-}
-
-fn check_if_other_figure_in_between(
-    board: &Chessboard,
-    moves: &Vec<usize>,
-    en_passanted: &usize,
-    own_position: &usize,
-) -> bool {
-    for single in moves {
-        // ignore both pawns involved in en passant
-        if single != en_passanted && single != own_position && board.positions.field_is_used(*single) {
-            return !board.is_queen_or_rook(board.get_opponent_color(), *single);
-        }
+    let own_king_position = own_king_board.get_first_field();
+    // only a rook or queen on the same row can give a check through both pawns
+    if Square::from_index(own_king_position).rank() != Square::from_index(*own_position).rank() {
+        return true;
     }
-    true
+    let opponent_color = board.get_opponent_color();
+    let row = line(own_king_position, *own_position);
+    let attackers = (*board.get_pieces(opponent_color, Piece::Rook)
+        | *board.get_pieces(opponent_color, Piece::Queen))
+        & row;
+
+    // ignore both pawns involved in en passant
+    let mut positions_after_en_passant = board.positions;
+    positions_after_en_passant.remove_field(*own_position);
+    positions_after_en_passant.remove_field(*en_passanted);
+
+    attackers
+        .into_iter()
+        .all(|attacker| !(between(own_king_position, attacker) & positions_after_en_passant).is_empty())
 }
 
 fn add_pawn_takes(
     board: &Chessboard,
     own_color: Color,
     own_position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>,
+    possible_moves: &mut SmallVec<[Move; 64]>,
 ) {
     let possible_takes = &PAWN_THREATS[own_color as usize][own_position];
 
-    let real_takes = Bitboard {
-        board: possible_takes.board & board.get_opponents().board,
-    };
-    let takes_with_promotion = Bitboard {
-        board: real_takes.board & PAWN_PROMOTION_FIELDS.board,
-    };
+    let real_takes = *possible_takes & *board.get_opponents();
+    let takes_with_promotion = real_takes & *PAWN_PROMOTION_FIELDS;
 
     // we either have takes with promotion or regular takes - never both
-    if takes_with_promotion.board != 0 {
+    if !takes_with_promotion.is_empty() {
         takes_with_promotion.iterate_board(|new_field| {
-            add_promotion_to_possible_moves(own_position, new_field, possible_moves)
+            add_promotion_to_possible_moves(own_position, new_field, true, possible_moves)
         });
     } else {
         real_takes.iterate_board(|new_field| {
-            possible_moves.push(PossibleMove {
-                from: own_position,
-                to: new_field,
-                promoted_to: None,
-            })
+            possible_moves.push(Move::new(own_position, new_field, MoveType::Capture))
         });
     }
 }
@@ -153,53 +128,21 @@ pub fn get_possible_pawn_moves(
     board: &Chessboard,
     own_position: usize,
     own_color: Color,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
 
     let one_step_forward = calculate_forward_position(own_position, own_color, 8);
-    if let Some(possible_en_passant) = board.en_passant {
-        if figure_can_move_left(own_position, &own_color) && en_passant_position_left(&own_position, own_color) == possible_en_passant && en_passant_no_check(board, &own_position, own_color, &possible_en_passant) {
-            let take_left_position = take_left_position(&one_step_forward, own_color);
-            possible_moves.push(PossibleMove {
-                from: own_position,
-                to: take_left_position,
-                promoted_to: None,
-            });
-        }
-        if figure_can_move_right(own_position, &own_color) {
-            if let Some(possible_en_passant) = board.en_passant {
-                if en_passant_position_right(&own_position, own_color) == possible_en_passant
-                    && en_passant_no_check(board, &own_position, own_color, &possible_en_passant)
-                {
-                    let take_right_position = take_right_position(&one_step_forward, own_color);
-                    possible_moves.push(PossibleMove {
-                        to: take_right_position,
-                        from: own_position,
-                        promoted_to: None,
-                    });
-                }
-            }
-        }
-    }
     // one field forward
     if !board.positions.field_is_used(one_step_forward){
         if !figure_will_promote(one_step_forward, &own_color) {
-            possible_moves.push(PossibleMove {
-                to: one_step_forward,
-                from: own_position,
-                promoted_to: None,
-            });
+            possible_moves.push(Move::new(own_position, one_step_forward, MoveType::Quiet));
         }
         // two fields forward
         if can_move_two_fields(own_position, own_color) {
             let two_steps_forward = calculate_forward_position(own_position, own_color, 16);
 
             if !board.positions.field_is_used(two_steps_forward) {
-                possible_moves.push(PossibleMove {
-                    to: two_steps_forward,
-                    from: own_position,
-                    promoted_to: None,
-                });
+                possible_moves.push(Move::new(own_position, two_steps_forward, MoveType::DoublePawnPush));
             }
         }
     }
@@ -208,28 +151,12 @@ pub fn get_possible_pawn_moves(
 fn add_promotion_to_possible_moves(
     old_field: usize,
     new_field: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    is_capture: bool,
+    possible_moves: &mut SmallVec<[Move; 64]>
 ) {
-    possible_moves.push(PossibleMove {
-        to: new_field,
-        from: old_field,
-        promoted_to: Some(Promotion::Queen),
-    });
-    possible_moves.push(PossibleMove {
-        to: new_field,
-        from: old_field,
-        promoted_to: Some(Promotion::Knight),
-    });
-    possible_moves.push(PossibleMove {
-        to: new_field,
-        from: old_field,
-        promoted_to: Some(Promotion::Bishop),
-    });
-    possible_moves.push(PossibleMove {
-        to: new_field,
-        from: old_field,
-        promoted_to: Some(Promotion::Rook),
-    });
+    for promotion in [Promotion::Queen, Promotion::Knight, Promotion::Bishop, Promotion::Rook] {
+        possible_moves.push(Move::promotion(old_field, new_field, promotion, is_capture));
+    }
 }
 
 pub fn get_fields_threatened_by_pawn(own_position: usize, own_color: Color) -> Bitboard {
@@ -240,14 +167,27 @@ pub fn get_possible_pawn_takes_and_promotion(
     board: &Chessboard,
     own_position: usize,
     own_color: Color,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>,
+    possible_moves: &mut SmallVec<[Move; 64]>,
 ) {
     add_pawn_takes(board, own_color, own_position, possible_moves);
     let one_step_forward = calculate_forward_position(own_position, own_color, 8);
+    if let Some(possible_en_passant) = board.en_passant {
+        if figure_can_move_left(own_position, &own_color) && en_passant_position_left(&own_position, own_color) == possible_en_passant && en_passant_no_check(board, &own_position, own_color, &possible_en_passant) {
+            let take_left_position = take_left_position(&one_step_forward, own_color);
+            possible_moves.push(Move::new(own_position, take_left_position, MoveType::EnPassant));
+        }
+        if figure_can_move_right(own_position, &own_color)
+            && en_passant_position_right(&own_position, own_color) == possible_en_passant
+            && en_passant_no_check(board, &own_position, own_color, &possible_en_passant)
+        {
+            let take_right_position = take_right_position(&one_step_forward, own_color);
+            possible_moves.push(Move::new(own_position, take_right_position, MoveType::EnPassant));
+        }
+    }
     if figure_will_promote(one_step_forward, &own_color)
         && !board.positions.field_is_used(one_step_forward)
     {
-        add_promotion_to_possible_moves(own_position, one_step_forward, possible_moves);
+        add_promotion_to_possible_moves(own_position, one_step_forward, false, possible_moves);
     }
 }
 
@@ -256,30 +196,22 @@ pub fn get_possible_pawn_moves_to_prevent_check(
     own_position: usize,
     own_color: Color,
     prevent_check_fields: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>,
+    possible_moves: &mut SmallVec<[Move; 64]>,
 ) {
     // Takes
     let possible_takes = &PAWN_THREATS[own_color as usize][own_position];
 
-    let real_takes = Bitboard {
-        board: possible_takes.board & board.get_opponents().board & prevent_check_fields.board,
-    };
-    let takes_with_promotion = Bitboard {
-        board: real_takes.board & PAWN_PROMOTION_FIELDS.board & prevent_check_fields.board,
-    };
+    let real_takes = *possible_takes & *board.get_opponents() & prevent_check_fields;
+    let takes_with_promotion = real_takes & *PAWN_PROMOTION_FIELDS;
 
     // we either have takes with promotion or regular takes - never both
-    if takes_with_promotion.board != 0 {
+    if !takes_with_promotion.is_empty() {
         takes_with_promotion.iterate_board(|new_field| {
-            add_promotion_to_possible_moves(own_position, new_field, possible_moves)
+            add_promotion_to_possible_moves(own_position, new_field, true, possible_moves)
         });
     } else {
         real_takes.iterate_board(|new_field| {
-            possible_moves.push(PossibleMove {
-                from: own_position,
-                to: new_field,
-                promoted_to: None,
-            })
+            possible_moves.push(Move::new(own_position, new_field, MoveType::Capture))
         });
     }
 
@@ -287,7 +219,7 @@ pub fn get_possible_pawn_moves_to_prevent_check(
     if let Some(possible_en_passant) = board.en_passant {
         // en passant can only prevent check if there is exactly one field we can prevent check
         // (and that is the figure checking aka pawn)
-        if prevent_check_fields.board.count_ones() == 1 {
+        if prevent_check_fields.len() == 1 {
             // both fields are null checked above
             let checked_by_field = prevent_check_fields.get_first_field();
             let en_passant_field = board.en_passant.unwrap();
@@ -300,11 +232,7 @@ pub fn get_possible_pawn_moves_to_prevent_check(
                             &possible_en_passant,
                         ) {
                     let take_left_position = take_left_position(&one_step_forward, own_color);
-                    possible_moves.push(PossibleMove {
-                        from: own_position,
-                        to: take_left_position,
-                        promoted_to: None,
-                    });
+                    possible_moves.push(Move::new(own_position, take_left_position, MoveType::EnPassant));
                 }
                 if figure_can_move_right(own_position, &own_color) {
                     if let Some(possible_en_passant) = board.en_passant {
@@ -319,11 +247,7 @@ pub fn get_possible_pawn_moves_to_prevent_check(
                         {
                             let take_right_position =
                                 take_right_position(&one_step_forward, own_color);
-                            possible_moves.push(PossibleMove {
-                                to: take_right_position,
-                                from: own_position,
-                                promoted_to: None,
-                            });
+                            possible_moves.push(Move::new(own_position, take_right_position, MoveType::EnPassant));
                         }
                     }
                 }
@@ -334,13 +258,9 @@ pub fn get_possible_pawn_moves_to_prevent_check(
     if !board.positions.field_is_used(one_step_forward) {
         if prevent_check_fields.field_is_used(one_step_forward) {
             if figure_will_promote(one_step_forward, &own_color) {
-                add_promotion_to_possible_moves(own_position, one_step_forward, possible_moves);
+                add_promotion_to_possible_moves(own_position, one_step_forward, false, possible_moves);
             } else {
-                possible_moves.push(PossibleMove {
-                    to: one_step_forward,
-                    from: own_position,
-                    promoted_to: None,
-                });
+                possible_moves.push(Move::new(own_position, one_step_forward, MoveType::Quiet));
             }
         }
 
@@ -349,11 +269,7 @@ pub fn get_possible_pawn_moves_to_prevent_check(
             let two_steps_forward = calculate_forward_position(own_position, own_color, 16);
 
             if prevent_check_fields.field_is_used(two_steps_forward) && !board.positions.field_is_used(two_steps_forward) {
-                possible_moves.push(PossibleMove {
-                    to: two_steps_forward,
-                    from: own_position,
-                    promoted_to: None,
-                });
+                possible_moves.push(Move::new(own_position, two_steps_forward, MoveType::DoublePawnPush));
             }
         }
     }
@@ -420,8 +336,8 @@ mod tests {
         board.used_positions[Color::Black as usize].set_field(34);
 
         let mut moves = SmallVec::new();
-        get_possible_pawn_moves(&board, 35, Color::White, &mut moves);
-        let move_fields: Vec<usize> = moves.into_iter().map(|x| x.to).collect();
+        get_possible_pawn_takes_and_promotion(&board, 35, Color::White, &mut moves);
+        let move_fields: Vec<usize> = moves.into_iter().map(|x| x.to()).collect();
         assert_eq!(true, move_fields.contains(&42));
     }
 
@@ -439,8 +355,8 @@ mod tests {
         board.figures[Color::White as usize][Piece::Pawn as usize].set_field(27);
 
         let mut moves = SmallVec::new();
-        get_possible_pawn_moves(&board, 26, Color::Black, &mut moves);
-        let move_fields: Vec<usize> = moves.into_iter().map(|x| x.to).collect();
+        get_possible_pawn_takes_and_promotion(&board, 26, Color::Black, &mut moves);
+        let move_fields: Vec<usize> = moves.into_iter().map(|x| x.to()).collect();
         assert_eq!(true, move_fields.contains(&19));
     }
 
@@ -465,8 +381,8 @@ mod tests {
         board.used_positions[Color::Black as usize].set_field(24);
 
         let mut moves = SmallVec::new();
-        get_possible_pawn_moves(&board, 26, Color::Black, &mut moves);
-        let move_fields: Vec<usize> = moves.into_iter().map(|x| x.to).collect();
+        get_possible_pawn_takes_and_promotion(&board, 26, Color::Black, &mut moves);
+        let move_fields: Vec<usize> = moves.into_iter().map(|x| x.to()).collect();
         assert_eq!(false, move_fields.contains(&19));
     }
 
@@ -6,4 +6,16 @@ pub enum Piece {
     Rook,
     Queen,
     King
-}
\ No newline at end of file
+}
+// same order as the bitboards of the chessboard
+pub const ALL_PIECES: [Piece; 6] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+    Piece::King,
+];
+
+// in centipawns (used for move ordering) - the king is never captured
+pub const PIECE_VALUES: [i32; 6] = [100, 300, 320, 500, 900, 0];
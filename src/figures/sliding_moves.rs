@@ -1,6 +1,9 @@
 use smallvec::SmallVec;
 
-use crate::{board::{bitboard::Bitboard, board::Chessboard}, engine::engine::PossibleMove, helper::magic_bitboards::helper::get_magic_index, BISHOP_MAGIC_BITBOARDS, BISHOP_MAGIC_POSITIONS, ROOK_MAGIC_BITBOARDS, ROOK_MAGIC_POSITIONS};
+#[cfg(feature = "pext")]
+use crate::helper::magic_bitboards::pext::{get_pext_bishop_attacks, get_pext_rook_attacks, use_pext};
+
+use crate::{board::{bitboard::Bitboard, board::Chessboard, chess_move::{add_quiet_moves_and_captures, Move, MoveType}}, helper::magic_bitboards::{helper::get_magic_index, tables::{BISHOP_MAGIC_BITBOARDS, ROOK_MAGIC_BITBOARDS, SLIDING_ATTACKS}}};
 
 pub fn get_fields_threatened_by_queen(
     board: &Chessboard,
@@ -9,7 +12,7 @@ pub fn get_fields_threatened_by_queen(
 ) -> Bitboard {
     let bishop_threats = get_fields_threatened_by_bishop(board, position, king_position);
     let rook_threats = get_fields_threatened_by_rook(board, position, king_position);
-    Bitboard { board: bishop_threats.board | rook_threats.board }
+    bishop_threats | rook_threats
 }
 
 
@@ -20,12 +23,20 @@ pub fn get_fields_threatened_by_bishop(
 ) -> Bitboard {
     let mut board_without_king = board.positions;
     board_without_king.remove_field(king_position);
+    get_bishop_attacks(position, board_without_king)
+}
 
-    let move_options = &BISHOP_MAGIC_POSITIONS[position];
-    let magic_options = &BISHOP_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board_without_king, magic_options)];
+// fields attacked from the position with the given blockers (e.g. to look through pieces for x-rays)
+pub fn get_bishop_attacks(position: usize, blockers: Bitboard) -> Bitboard {
+    #[cfg(feature = "pext")]
+    if use_pext() {
+        return get_pext_bishop_attacks(position, blockers);
+    }
+    get_magic_bishop_attacks(position, blockers)
+}
 
-    Bitboard{board:magic_moves.board}
+pub fn get_magic_bishop_attacks(position: usize, blockers: Bitboard) -> Bitboard {
+    Bitboard { board: SLIDING_ATTACKS[get_magic_index(blockers, &BISHOP_MAGIC_BITBOARDS[position])] }
 }
 
 pub fn get_fields_threatened_by_rook(
@@ -36,18 +47,25 @@ pub fn get_fields_threatened_by_rook(
     // remove own king to prevent bug where king moves on same line as attacker "as field is not attacked" (he is blocking it himself)
     let mut board_without_king = board.positions;
     board_without_king.remove_field(king_position);
+    get_rook_attacks(position, board_without_king)
+}
 
-    let move_options = &ROOK_MAGIC_POSITIONS[position];
-    let magic_options = &ROOK_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board_without_king, magic_options)];
+pub fn get_rook_attacks(position: usize, blockers: Bitboard) -> Bitboard {
+    #[cfg(feature = "pext")]
+    if use_pext() {
+        return get_pext_rook_attacks(position, blockers);
+    }
+    get_magic_rook_attacks(position, blockers)
+}
 
-    Bitboard{board:magic_moves.board}
+pub fn get_magic_rook_attacks(position: usize, blockers: Bitboard) -> Bitboard {
+    Bitboard { board: SLIDING_ATTACKS[get_magic_index(blockers, &ROOK_MAGIC_BITBOARDS[position])] }
 }
 
 pub fn get_possible_queen_moves(
     board: &Chessboard,
     position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ) {
     get_possible_bishop_moves(board, position, possible_moves);
     get_possible_rook_moves(board, position, possible_moves);
@@ -57,87 +75,75 @@ pub fn get_possible_queen_moves(
 pub fn get_possible_bishop_moves(
     board: &Chessboard,
     position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
-    let move_options = &BISHOP_MAGIC_POSITIONS[position];
-    let magic_options = &BISHOP_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board.positions, magic_options)];
-    let moves = Bitboard{board:magic_moves.board & !board.positions.board};
-    moves.iterate_board(|mov| possible_moves.push(PossibleMove { from: position, to: mov, promoted_to: None }));
+    let magic_moves = get_bishop_attacks(position, board.positions);
+    let moves = magic_moves & !board.positions;
+    moves.iterate_board(|mov| possible_moves.push(Move::new(position, mov, MoveType::Quiet)));
 }
 
 pub fn get_possible_rook_moves(
     board: &Chessboard,
     position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ) {
-    let move_options = &ROOK_MAGIC_POSITIONS[position];
-    let magic_options = &ROOK_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board.positions, magic_options)];
-    let moves = Bitboard{board:magic_moves.board & !board.positions.board};
-    moves.iterate_board(|mov| possible_moves.push(PossibleMove { from: position, to: mov, promoted_to: None }));
+    let magic_moves = get_rook_attacks(position, board.positions);
+    let moves = magic_moves & !board.positions;
+    moves.iterate_board(|mov| possible_moves.push(Move::new(position, mov, MoveType::Quiet)));
 }
 
 pub fn get_possible_rook_moves_to_prevent_check(
     board: &Chessboard,
     position: usize,
     prevent_check_fields: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>,
+    possible_moves: &mut SmallVec<[Move; 64]>,
 ){
-    let move_options = &ROOK_MAGIC_POSITIONS[position];
-    let magic_options = &ROOK_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board.positions, magic_options)];
+    let magic_moves = get_rook_attacks(position, board.positions);
 
-    let moves = Bitboard{board:magic_moves.board & prevent_check_fields.board};
-    moves.iterate_board(|mov| possible_moves.push(PossibleMove { from: position, to: mov, promoted_to: None }));
+    let moves = magic_moves & prevent_check_fields;
+    add_quiet_moves_and_captures(board, position, moves, possible_moves);
 }
 
 pub fn get_possible_bishop_takes(
     board: &Chessboard,
     position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
-    let move_options = &BISHOP_MAGIC_POSITIONS[position];
-    let magic_options = &BISHOP_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board.positions, magic_options)];
+    let magic_moves = get_bishop_attacks(position, board.positions);
 
-    let moves = Bitboard{board:magic_moves.board & board.get_opponents().board};
+    let moves = magic_moves & *board.get_opponents();
 
-    moves.iterate_board(|mov| possible_moves.push(PossibleMove { from: position, to: mov, promoted_to: None }));
+    moves.iterate_board(|mov| possible_moves.push(Move::new(position, mov, MoveType::Capture)));
 }
 
 pub fn get_possible_bishop_moves_to_prevent_check(
     board: &Chessboard,
     position: usize,
     prevent_check_fields: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>,
+    possible_moves: &mut SmallVec<[Move; 64]>,
 ){
-    let move_options = &BISHOP_MAGIC_POSITIONS[position];
-    let magic_options = &BISHOP_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board.positions, magic_options)];
+    let magic_moves = get_bishop_attacks(position, board.positions);
 
-    let moves = Bitboard{board:magic_moves.board & prevent_check_fields.board};
-    moves.iterate_board(|mov| possible_moves.push(PossibleMove { from: position, to: mov, promoted_to: None }));
+    let moves = magic_moves & prevent_check_fields;
+    add_quiet_moves_and_captures(board, position, moves, possible_moves);
 }
 
 pub fn get_possible_rook_takes(
     board: &Chessboard,
     position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
-    let move_options = &ROOK_MAGIC_POSITIONS[position];
-    let magic_options = &ROOK_MAGIC_BITBOARDS[position];
-    let magic_moves = move_options[get_magic_index(board.positions, magic_options)];
+    let magic_moves = get_rook_attacks(position, board.positions);
 
-    let moves = Bitboard{board:magic_moves.board & board.get_opponents().board};
+    let moves = magic_moves & *board.get_opponents();
 
-    moves.iterate_board(|mov| possible_moves.push(PossibleMove { from: position, to: mov, promoted_to: None }));
+    moves.iterate_board(|mov| possible_moves.push(Move::new(position, mov, MoveType::Capture)));
 }
 
 pub fn get_possible_queen_takes(
     board: &Chessboard,
     position: usize,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>
+    possible_moves: &mut SmallVec<[Move; 64]>
 ){
     get_possible_rook_takes(board, position, possible_moves);
     get_possible_bishop_takes(board, position, possible_moves);
@@ -147,7 +153,7 @@ pub fn get_possible_queen_moves_to_prevent_check(
     board: &Chessboard,
     position: usize,
     prevent_check_fields: Bitboard,
-    possible_moves: &mut SmallVec<[PossibleMove; 64]>,
+    possible_moves: &mut SmallVec<[Move; 64]>,
 ){
     get_possible_rook_moves_to_prevent_check(board, position, prevent_check_fields, possible_moves);
     get_possible_bishop_moves_to_prevent_check(board, position, prevent_check_fields, possible_moves);
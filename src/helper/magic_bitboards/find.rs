@@ -5,70 +5,95 @@ use crate::{board::bitboard::Bitboard, helper::moves_by_field::{get_bishop_block
 
 use super::{helper::{get_magic_index, get_valid_moves_for_position_with_given_blockers}, magic_bitboard::MagicBitboard};
 
-#[allow(dead_code)]
-pub fn init_bishop_magics() -> ([MagicBitboard; 64], [Vec<Bitboard>; 64]) {
-    let mut magic_bitboards = [MagicBitboard{..Default::default()}; 64];
-    let mut magic_positions = [const { Vec::new() }; 64];
+// magics [rook | bishop] [reduce <bits>] [tries <n>]
+// searches new magics and prints them as constants for build/magic_numbers.rs
+// with reduce the tables get smaller (bits less than relevant fields) - fields without a magic after n tries use less reduction
+pub fn run_magic_search(commands: &[&str]) {
+    let reduce_bits = get_option(commands, "reduce").unwrap_or(0) as u8;
+    let max_tries = get_option(commands, "tries").unwrap_or(1_000_000);
+    let only = commands.get(1).copied().filter(|piece| *piece == "rook" || *piece == "bishop");
+    if only != Some("bishop") {
+        print_magics("ROOK", &find_magics(true, reduce_bits, max_tries));
+    }
+    if only != Some("rook") {
+        print_magics("BISHOP", &find_magics(false, reduce_bits, max_tries));
+    }
+}
 
-    let possible_moves: FxHashMap<usize, MoveInEveryDirection> = get_moves_for_each_field();
-    for column in 0..8 {
-        for row in 0..8 {
-            let position: usize = column *8 + row;
-            let blockers = get_bishop_blockers_for_field(column, row);
-            let (magic_bitboard, positions) =
-                find_magic(blockers, position, &possible_moves, false);
-            magic_bitboards[position] = magic_bitboard;
-            magic_positions[position] = positions;
-        }
+fn get_option(commands: &[&str], key: &str) -> Option<usize> {
+    let index = commands.iter().position(|x| *x == key)?;
+    commands.get(index + 1)?.parse().ok()
+}
+
+fn print_magics(name: &str, magics: &[MagicBitboard; 64]) {
+    let table_size: usize = magics.iter().map(|magic| 1 << (64 - magic.index)).sum();
+    println!("// {} table entries", table_size);
+    println!("pub const {}_MAGIC_NUMBERS: [u64; 64] = [", name);
+    for magic in magics {
+        println!("    {},", magic.magic_key);
+    }
+    println!("];");
+    println!("pub const {}_INDEX_BITS: [u8; 64] = [", name);
+    for row in magics.chunks(8) {
+        let bits: Vec<String> = row.iter().map(|magic| (64 - magic.index).to_string()).collect();
+        println!("    {},", bits.join(", "));
     }
-    (magic_bitboards, magic_positions)
+    println!("];");
 }
 
-#[allow(dead_code)]
-pub fn init_rook_magics() -> ([MagicBitboard; 64], [Vec<Bitboard>; 64]) {
+pub fn find_magics(is_rook: bool, reduce_bits: u8, max_tries: usize) -> [MagicBitboard; 64] {
     let mut magic_bitboards = [MagicBitboard{..Default::default()}; 64];
     let possible_moves: FxHashMap<usize, MoveInEveryDirection> = get_moves_for_each_field();
-    let mut magic_positions = [const { Vec::new() }; 64];
     for column in 0..8 {
         for row in 0..8 {
             let position: usize = column *8 + row;
-            let blockers = get_rook_blockers_for_field(column, row);
-            let (magic_bitboard, positions) =
-                find_magic(blockers, position, &possible_moves, true);
-            magic_bitboards[position] = magic_bitboard;
-            magic_positions[position] = positions;
+            let blockers = if is_rook {
+                get_rook_blockers_for_field(column, row)
+            } else {
+                get_bishop_blockers_for_field(column, row)
+            };
+            let relevant_bits = blockers.len() as u8;
+            // without reduction there is always a magic - no limit needed
+            magic_bitboards[position] = (0..=reduce_bits.min(relevant_bits))
+                .rev()
+                .find_map(|reduce| {
+                    let tries = if reduce == 0 { usize::MAX } else { max_tries };
+                    find_magic(blockers, position, &possible_moves, is_rook, relevant_bits - reduce, tries)
+                })
+                .unwrap();
         }
     }
-    (magic_bitboards, magic_positions)
+    magic_bitboards
 }
 
 fn find_magic(
     blockers: Bitboard,
     position: usize,
     moves_by_field: &FxHashMap<usize, MoveInEveryDirection>,
-    is_rook: bool
-) -> (MagicBitboard, Vec<Bitboard>) {
+    is_rook: bool,
+    index_bits: u8,
+    max_tries: usize,
+) -> Option<MagicBitboard> {
     let mut rng = rand::rng();
-    let shift: u8 = 64 - blockers.get_used_fields().len() as u8;
-    loop {
+    (0..max_tries).find_map(|_| {
         let magic = rng.random::<u64>() & rng.random::<u64>() & rng.random::<u64>();
         let magic_bitboard = MagicBitboard {
             relevant_fields: blockers,
             magic_key: magic,
-            index: shift,
+            index: 64 - index_bits,
+            offset: 0,
         };
-        if let Some(valid) = create_possible_moves_vec(&magic_bitboard, position, moves_by_field, is_rook) {
-            return (magic_bitboard, valid);
-        }
-    }
+        is_collision_free(&magic_bitboard, position, moves_by_field, is_rook).then_some(magic_bitboard)
+    })
 }
 
-fn create_possible_moves_vec(
+// different blockers may share an index - but only if they result in the same moves
+fn is_collision_free(
     magic_bitboard: &MagicBitboard,
     own_position: usize,
     moves_by_field: &FxHashMap<usize, MoveInEveryDirection>,
     is_rook: bool
-) -> Option<Vec<Bitboard>> {
+) -> bool {
     let index_bits = 64 - magic_bitboard.index;
     let mut table = vec![Bitboard::new(); 1 << index_bits];
     let mut blockers = Bitboard::new();
@@ -78,7 +103,7 @@ fn create_possible_moves_vec(
         if table_entry.board == 0 {
             *table_entry = moves;
         } else if table_entry.board != moves.board {
-            return None;
+            return false;
         }
         blockers.board = blockers
             .board
@@ -88,5 +113,54 @@ fn create_possible_moves_vec(
             break;
         }
     }
-    Some(table)
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::square::Square,
+        figures::sliding_moves::{get_magic_bishop_attacks, get_magic_rook_attacks},
+    };
+
+    use super::*;
+
+    // every subset of the relevant fields gives the same moves as walking the rays
+    // (the magic tables themselves - get_rook_attacks would use pext if it is enabled)
+    #[test]
+    fn test_predefined_magics_match_reference_moves() {
+        let moves_by_field = get_moves_for_each_field();
+        for position in 0..64 {
+            let square = Square::from_index(position);
+            for is_rook in [true, false] {
+                let relevant_fields = if is_rook {
+                    get_rook_blockers_for_field(square.rank().index(), square.file().index())
+                } else {
+                    get_bishop_blockers_for_field(square.rank().index(), square.file().index())
+                };
+                let mut blockers = Bitboard::new();
+                loop {
+                    let expected = get_valid_moves_for_position_with_given_blockers(blockers, position, &moves_by_field, is_rook);
+                    let magic_moves = if is_rook {
+                        get_magic_rook_attacks(position, blockers)
+                    } else {
+                        get_magic_bishop_attacks(position, blockers)
+                    };
+                    assert_eq!(expected, magic_moves, "field {} rook {}", position, is_rook);
+                    blockers.board = blockers.board.wrapping_sub(relevant_fields.board) & relevant_fields.board;
+                    if blockers.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_found_magics_are_collision_free() {
+        let moves_by_field = get_moves_for_each_field();
+        for (position, magic) in find_magics(false, 0, 1).iter().enumerate() {
+            assert!(is_collision_free(magic, position, &moves_by_field, false));
+        }
+    }
 }
@@ -11,7 +11,7 @@ pub fn get_magic_index(blockers: Bitboard, magic_bitboard: &MagicBitboard) -> us
         board: blockers.board & magic_bitboard.relevant_fields.board,
     };
     let hash = relevant_pieces.board.wrapping_mul(magic_bitboard.magic_key);
-    (hash >> magic_bitboard.index) as usize
+    magic_bitboard.offset + (hash >> magic_bitboard.index) as usize
 }
 
 fn itter_direction(
@@ -39,13 +39,13 @@ pub fn init_king_safety_bitboards() -> [[Bitboard; 3]; 2]{
     return_value[Color::White as usize][KingPosition::RIGHT as usize].set_field(14);
     return_value[Color::White as usize][KingPosition::RIGHT as usize].set_field(15);
 
-    return_value[Color::Black as usize][KingPosition::LEFT as usize].set_field(53);
-    return_value[Color::Black as usize][KingPosition::LEFT as usize].set_field(54);
-    return_value[Color::Black as usize][KingPosition::LEFT as usize].set_field(55);
+    return_value[Color::Black as usize][KingPosition::LEFT as usize].set_field(48);
+    return_value[Color::Black as usize][KingPosition::LEFT as usize].set_field(49);
+    return_value[Color::Black as usize][KingPosition::LEFT as usize].set_field(50);
 
-    return_value[Color::Black as usize][KingPosition::RIGHT as usize].set_field(48);
-    return_value[Color::Black as usize][KingPosition::RIGHT as usize].set_field(49);
-    return_value[Color::Black as usize][KingPosition::RIGHT as usize].set_field(50);
+    return_value[Color::Black as usize][KingPosition::RIGHT as usize].set_field(53);
+    return_value[Color::Black as usize][KingPosition::RIGHT as usize].set_field(54);
+    return_value[Color::Black as usize][KingPosition::RIGHT as usize].set_field(55);
 
     return_value
 }
@@ -5,6 +5,8 @@ pub struct MagicBitboard {
     pub relevant_fields: Bitboard,
     pub magic_key: u64,
     pub index: u8,
+    // start of the fields attacks in the flat attack table
+    pub offset: usize,
 }
 
 impl Default for MagicBitboard {
@@ -12,7 +14,8 @@ impl Default for MagicBitboard {
         MagicBitboard {
             relevant_fields: Bitboard::new(),
             magic_key: 0,
-            index: 0
+            index: 0,
+            offset: 0,
         }
     }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 pub mod find;
 pub mod magic_bitboard;
-pub mod init_with_predefined;
+pub mod tables;
+#[cfg(feature = "pext")]
+pub mod pext;
 pub mod helper;
\ No newline at end of file
@@ -0,0 +1,87 @@
+use once_cell::sync::Lazy;
+
+use crate::board::bitboard::Bitboard;
+
+use super::tables::{BISHOP_MAGIC_BITBOARDS, ROOK_MAGIC_BITBOARDS};
+
+// generated by build.rs - offsets of every field and one flat attack table (rooks first, then bishops)
+include!(concat!(env!("OUT_DIR"), "/pext_tables.rs"));
+
+// checked once - without bmi2 (or on other architectures) the magics are used
+static USE_PEXT: Lazy<bool> = Lazy::new(is_bmi2_available);
+
+#[cfg(target_arch = "x86_64")]
+fn is_bmi2_available() -> bool {
+    is_x86_feature_detected!("bmi2")
+}
+
+// build with -C target-cpu=native (or target-feature=+bmi2) to skip the runtime check and inline the instruction
+pub fn use_pext() -> bool {
+    cfg!(target_feature = "bmi2") || *USE_PEXT
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn is_bmi2_available() -> bool {
+    false
+}
+
+// only call if use_pext() is true
+pub fn get_pext_rook_attacks(position: usize, blockers: Bitboard) -> Bitboard {
+    let index = pext(blockers.board, ROOK_MAGIC_BITBOARDS[position].relevant_fields.board);
+    Bitboard { board: PEXT_ATTACKS[ROOK_PEXT_OFFSETS[position] + index as usize] }
+}
+
+// only call if use_pext() is true
+pub fn get_pext_bishop_attacks(position: usize, blockers: Bitboard) -> Bitboard {
+    let index = pext(blockers.board, BISHOP_MAGIC_BITBOARDS[position].relevant_fields.board);
+    Bitboard { board: PEXT_ATTACKS[BISHOP_PEXT_OFFSETS[position] + index as usize] }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn pext(value: u64, mask: u64) -> u64 {
+    #[target_feature(enable = "bmi2")]
+    unsafe fn pext_bmi2(value: u64, mask: u64) -> u64 {
+        std::arch::x86_64::_pext_u64(value, mask)
+    }
+    debug_assert!(use_pext());
+    // safe as the attacks are only looked up with pext if the cpu supports bmi2
+    unsafe { pext_bmi2(value, mask) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn pext(_value: u64, _mask: u64) -> u64 {
+    unreachable!("pext is only available on x86_64")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::figures::sliding_moves::{get_magic_bishop_attacks, get_magic_rook_attacks};
+
+    use super::*;
+
+    #[test]
+    fn test_pext_matches_magics() {
+        if !use_pext() {
+            return;
+        }
+        for position in 0..64 {
+            for (relevant_fields, is_rook) in [
+                (ROOK_MAGIC_BITBOARDS[position].relevant_fields, true),
+                (BISHOP_MAGIC_BITBOARDS[position].relevant_fields, false),
+            ] {
+                let mut blockers = Bitboard::new();
+                loop {
+                    if is_rook {
+                        assert_eq!(get_magic_rook_attacks(position, blockers), get_pext_rook_attacks(position, blockers));
+                    } else {
+                        assert_eq!(get_magic_bishop_attacks(position, blockers), get_pext_bishop_attacks(position, blockers));
+                    }
+                    blockers.board = blockers.board.wrapping_sub(relevant_fields.board) & relevant_fields.board;
+                    if blockers.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
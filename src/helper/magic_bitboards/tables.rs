@@ -0,0 +1,36 @@
+use crate::board::bitboard::Bitboard;
+
+use super::magic_bitboard::MagicBitboard;
+
+// generated by build.rs - magics of every field and one flat attack table (rooks first, then bishops)
+include!(concat!(env!("OUT_DIR"), "/magic_tables.rs"));
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::square::Square,
+        helper::moves_by_field::{get_bishop_blockers_for_field, get_rook_blockers_for_field},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_relevant_fields_match_blockers() {
+        for position in 0..64 {
+            let square = Square::from_index(position);
+            let (column, row) = (square.rank().index(), square.file().index());
+            assert_eq!(get_rook_blockers_for_field(column, row), ROOK_MAGIC_BITBOARDS[position].relevant_fields);
+            assert_eq!(get_bishop_blockers_for_field(column, row), BISHOP_MAGIC_BITBOARDS[position].relevant_fields);
+        }
+    }
+
+    #[test]
+    fn test_fields_follow_each_other_in_table() {
+        let mut offset = 0;
+        for magic in ROOK_MAGIC_BITBOARDS.iter().chain(BISHOP_MAGIC_BITBOARDS.iter()) {
+            assert_eq!(offset, magic.offset);
+            offset += 1 << (64 - magic.index);
+        }
+        assert_eq!(SLIDING_ATTACKS.len(), offset);
+    }
+}
@@ -1,5 +1,5 @@
-pub mod movement;
 pub mod moves_by_field;
 pub mod magic_bitboards;
 pub mod opening;
+pub mod pgn;
 pub mod position_to_usize;
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::cmp::min;
 
 use rustc_hash::FxHashMap;
 
-use crate::{board::bitboard::Bitboard, figures::color::Color};
+use crate::{board::{bitboard::Bitboard, square::Square}, figures::color::Color};
 
 /*
  calculate all maximum possible moves in every direction once and then just use them when calculating the moves
@@ -90,6 +90,49 @@ pub fn get_moves_for_each_field() -> FxHashMap<usize, MoveInEveryDirection> {
     values
 }
 
+// (file, rank) steps - opposite directions are next to each other
+const DIRECTIONS: [(i8, i8); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, -1), (1, -1), (-1, 1)];
+
+// all fields reached from the field going into the direction (field itself not included)
+fn get_ray(field: usize, (file_delta, rank_delta): (i8, i8)) -> Bitboard {
+    let mut ray = Bitboard::new();
+    let mut current = Square::from_index(field);
+    while let Some(next) = current.offset(file_delta, rank_delta) {
+        ray.set_field(next.index());
+        current = next;
+    }
+    ray
+}
+
+pub fn get_between_fields() -> [[Bitboard; 64]; 64] {
+    let mut fields = [[Bitboard::new(); 64]; 64];
+    for (from, between_fields) in fields.iter_mut().enumerate() {
+        for (index, direction) in DIRECTIONS.iter().enumerate() {
+            let opposite_direction = DIRECTIONS[index ^ 1];
+            for to in get_ray(from, *direction) {
+                // walk from both fields towards each other
+                between_fields[to] = get_ray(from, *direction) & get_ray(to, opposite_direction);
+            }
+        }
+    }
+    fields
+}
+
+pub fn get_line_fields() -> [[Bitboard; 64]; 64] {
+    let mut fields = [[Bitboard::new(); 64]; 64];
+    for (from, line_fields) in fields.iter_mut().enumerate() {
+        for directions in DIRECTIONS.chunks(2) {
+            let rays = get_ray(from, directions[0]) | get_ray(from, directions[1]);
+            let mut line = rays;
+            line.set_field(from);
+            for to in rays {
+                line_fields[to] = line;
+            }
+        }
+    }
+    fields
+}
+
 pub fn get_pawn_promotion_moves() -> Bitboard{
     let mut board = Bitboard::new();
 
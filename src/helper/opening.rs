@@ -5,12 +5,14 @@ use std::{
 use dashmap::DashMap;
 use log::info;
 
-use crate::{board::board::Chessboard, helper::position_to_usize::get_values_from_input};
+use crate::{
+    board::{board::Chessboard, chess_move::Move},
+    helper::position_to_usize::get_values_from_input,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct OpeningMove {
-    pub from: usize,
-    pub to: usize,
+    pub mov: Move,
     pub count: u32
 }
 
@@ -30,7 +32,8 @@ pub fn create_opening_map() -> DashMap<u64, Vec<OpeningMove>> {
                 openings.insert(board.zobrist_key, Vec::new());
             } else {
                 let (from, to, count) = get_values_from_input(line);
-                openings.entry(board.zobrist_key).or_insert(Vec::new()).push(OpeningMove { from, to, count });
+                let mov = board.create_move(from, to, None);
+                openings.entry(board.zobrist_key).or_insert(Vec::new()).push(OpeningMove { mov, count });
             }
         }
     } else {
@@ -0,0 +1,483 @@
+use std::time::Duration;
+
+use crate::{
+    board::{
+        board::Chessboard,
+        chess_move::Move,
+        promotion::convert_promotion_to_output_string,
+        square::{File, Square},
+    },
+    engine::moves::get_valid_moves_in_position,
+    figures::{color::Color, piece::Piece},
+};
+
+const DEFAULT_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+// export format recommends lines shorter than 80 characters
+const MAX_LINE_LENGTH: usize = 79;
+
+// engine information written as comment behind a move e.g. {+0.35/12 1.2s}
+#[derive(Debug, Clone, Copy)]
+pub struct MoveComment {
+    pub evaluation: f32,
+    pub depth: u8,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct PgnMove {
+    // moves are stored the way we get them from the GUI (e.g. e2e4) and converted to SAN on export
+    pub uci: String,
+    pub comment: Option<MoveComment>,
+    // alternative lines starting from the position before this move (e.g. the line the engine expected instead)
+    pub variations: Vec<Vec<String>>,
+    // the continuation the engine expected after this move (its pv without the move itself)
+    pub expected_line: Vec<String>,
+}
+
+impl PgnMove {
+    pub fn new(uci: &str, comment: Option<MoveComment>) -> PgnMove {
+        PgnMove {
+            uci: String::from(uci),
+            comment,
+            variations: Vec::new(),
+            expected_line: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    // Seven Tag Roster
+    pub event: String,
+    pub site: String,
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    pub result: String,
+    // FEN of the start position - None if game started from default position
+    pub start_position: Option<String>,
+    pub moves: Vec<PgnMove>,
+}
+
+impl Default for PgnGame {
+    fn default() -> PgnGame {
+        PgnGame {
+            event: String::from("?"),
+            site: String::from("?"),
+            date: String::from("????.??.??"),
+            round: String::from("?"),
+            white: String::from("?"),
+            black: String::from("?"),
+            result: String::from("*"),
+            start_position: None,
+            moves: Vec::new(),
+        }
+    }
+}
+
+impl PgnGame {
+    pub fn new(start_position: Option<String>) -> PgnGame {
+        PgnGame {
+            start_position,
+            ..Default::default()
+        }
+    }
+
+    // if another move than the expected one is played, the expected line becomes a variation of it
+    pub fn add_move(&mut self, mut played_move: PgnMove) {
+        if let Some(last_move) = self.moves.last() {
+            if last_move.expected_line.first().is_some_and(|expected| *expected != played_move.uci) {
+                played_move.variations.push(last_move.expected_line.clone());
+            }
+        }
+        self.moves.push(played_move);
+    }
+
+    // sets the continuation expected after the last played move - ignored if there is no move yet
+    pub fn set_expected_line(&mut self, line: Vec<String>) {
+        if let Some(last_move) = self.moves.last_mut() {
+            last_move.expected_line = line;
+        }
+    }
+
+    // GUI sends all moves on every position update - keep known moves (and their comments) and only add new ones
+    pub fn sync_moves(&mut self, uci_moves: &[&str]) {
+        let known_moves = self
+            .moves
+            .iter()
+            .zip(uci_moves.iter())
+            .take_while(|(known, new)| known.uci == **new)
+            .count();
+        self.moves.truncate(known_moves);
+        for single in uci_moves.iter().skip(known_moves) {
+            self.add_move(PgnMove::new(single, None));
+        }
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        pgn.push_str(&format_tag("Event", &self.event));
+        pgn.push_str(&format_tag("Site", &self.site));
+        pgn.push_str(&format_tag("Date", &self.date));
+        pgn.push_str(&format_tag("Round", &self.round));
+        pgn.push_str(&format_tag("White", &self.white));
+        pgn.push_str(&format_tag("Black", &self.black));
+        pgn.push_str(&format_tag("Result", &self.result));
+        if let Some(fen) = &self.start_position {
+            pgn.push_str(&format_tag("SetUp", "1"));
+            pgn.push_str(&format_tag("FEN", fen));
+        }
+        pgn.push('\n');
+
+        let mut tokens = self.get_movetext_tokens();
+        tokens.push(self.result.clone());
+        pgn.push_str(&wrap_tokens(tokens));
+        pgn.push('\n');
+        pgn
+    }
+
+    fn get_movetext_tokens(&self) -> Vec<String> {
+        let fen = self
+            .start_position
+            .clone()
+            .unwrap_or(String::from(DEFAULT_POSITION));
+        let mut board = Chessboard {
+            ..Default::default()
+        };
+        board.create_position_from_input_string(fen.clone());
+        let mut move_number = get_fullmove_number(&fen);
+
+        let mut tokens = Vec::new();
+        // black needs a move number if it is the first move or the prev. move was interrupted by comment or variation
+        let mut needs_move_number = true;
+        for single in self.moves.iter() {
+            let Some(mov) = board.get_move_from_uci_input(&single.uci) else {
+                break;
+            };
+            tokens.push(get_numbered_san(&board, mov, move_number, needs_move_number));
+            if let Some(comment) = single.comment {
+                tokens.push(format_comment(comment));
+            }
+            for variation in single.variations.iter() {
+                tokens.extend(get_variation_tokens(&board, variation, move_number));
+            }
+            needs_move_number = single.comment.is_some() || !single.variations.is_empty();
+
+            if board.current_move == Color::Black {
+                move_number += 1;
+            }
+            board.move_figure(mov);
+        }
+        tokens
+    }
+}
+
+fn get_variation_tokens(board: &Chessboard, line: &[String], mut move_number: u32) -> Vec<String> {
+    let mut board = *board;
+    let mut tokens = Vec::new();
+    for (index, single) in line.iter().enumerate() {
+        let Some(mov) = board.get_move_from_uci_input(single) else {
+            break;
+        };
+        tokens.push(get_numbered_san(&board, mov, move_number, index == 0));
+        if board.current_move == Color::Black {
+            move_number += 1;
+        }
+        board.move_figure(mov);
+    }
+    if tokens.is_empty() {
+        return tokens;
+    }
+    // parenthesis are attached to first and last move
+    tokens[0].insert(0, '(');
+    if let Some(last) = tokens.last_mut() {
+        last.push(')');
+    }
+    tokens
+}
+
+fn get_numbered_san(
+    board: &Chessboard,
+    mov: Move,
+    move_number: u32,
+    black_needs_number: bool,
+) -> String {
+    let san = convert_move_to_san(board, mov);
+    match board.current_move {
+        Color::White => format!("{}. {}", move_number, san),
+        Color::Black if black_needs_number => format!("{}... {}", move_number, san),
+        Color::Black => san,
+    }
+}
+
+fn format_tag(name: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[{} \"{}\"]\n", name, escaped)
+}
+
+fn format_comment(comment: MoveComment) -> String {
+    format!(
+        "{{{:+.2}/{} {:.1}s}}",
+        comment.evaluation,
+        comment.depth,
+        comment.time.as_secs_f32()
+    )
+}
+
+fn get_fullmove_number(fen: &str) -> u32 {
+    fen.split_whitespace()
+        .nth(5)
+        .and_then(|number| number.parse().ok())
+        .unwrap_or(1)
+}
+
+fn wrap_tokens(tokens: Vec<String>) -> String {
+    let mut text = String::new();
+    let mut line_length = 0;
+    // a token may contain spaces (move numbers, comments) - split them to wrap correctly
+    for word in tokens.iter().flat_map(|token| token.split(' ')) {
+        if line_length > 0 && line_length + word.len() + 1 > MAX_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            text.push(' ');
+            line_length += 1;
+        }
+        text.push_str(word);
+        line_length += word.len();
+    }
+    text
+}
+
+fn get_piece_letter(piece: Piece) -> &'static str {
+    match piece {
+        Piece::Pawn => "",
+        Piece::Knight => "N",
+        Piece::Bishop => "B",
+        Piece::Rook => "R",
+        Piece::Queen => "Q",
+        Piece::King => "K",
+    }
+}
+
+// Standard Algebraic Notation for a legal move in the given position
+pub fn convert_move_to_san(board: &Chessboard, mov: Move) -> String {
+    let piece = board
+        .get_type_of_figure(board.current_move, mov.from())
+        .unwrap_or(Piece::Pawn);
+    let mut san = String::new();
+
+    if mov.is_castle() {
+        if Square::from_index(mov.to()).file() == File::G {
+            san.push_str("O-O");
+        } else {
+            san.push_str("O-O-O");
+        }
+    } else {
+        let from = Square::from_index(mov.from());
+
+        san.push_str(get_piece_letter(piece));
+        if piece == Piece::Pawn {
+            if mov.is_capture() {
+                san.push(from.file().to_char());
+            }
+        } else {
+            san.push_str(&get_disambiguation(board, mov, piece, from));
+        }
+        if mov.is_capture() {
+            san.push('x');
+        }
+        san.push_str(&Square::from_index(mov.to()).to_string());
+        if mov.is_promotion() {
+            san.push('=');
+            san.push_str(&convert_promotion_to_output_string(mov.promoted_to()));
+        }
+    }
+
+    let mut new_board = *board;
+    new_board.move_figure(mov);
+    let (opponent_moves, is_in_check) = get_valid_moves_in_position(&new_board, true);
+    if is_in_check && opponent_moves.is_empty() {
+        san.push('#');
+    } else if is_in_check {
+        san.push('+');
+    }
+    san
+}
+
+// add file, rank or both if another piece of the same type can move to the same field
+fn get_disambiguation(board: &Chessboard, mov: Move, piece: Piece, from: Square) -> String {
+    let (valid_moves, _) = get_valid_moves_in_position(board, true);
+    let others: Vec<Square> = valid_moves
+        .iter()
+        .filter(|single| {
+            single.to() == mov.to()
+                && single.from() != mov.from()
+                && board.get_pieces(board.current_move, piece).field_is_used(single.from())
+        })
+        .map(|single| Square::from_index(single.from()))
+        .collect();
+
+    if others.is_empty() {
+        return String::new();
+    }
+    if others.iter().all(|other| other.file() != from.file()) {
+        return from.file().to_string();
+    }
+    if others.iter().all(|other| other.rank() != from.rank()) {
+        return from.rank().to_string();
+    }
+    from.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn san_for_position(fen: &str, uci: &str) -> String {
+        let board = Chessboard::from_fen(fen);
+        convert_move_to_san(&board, board.get_move_from_uci_input(uci).unwrap())
+    }
+
+    #[test]
+    fn test_simple_san() {
+        assert_eq!("e4", san_for_position(DEFAULT_POSITION, "e2e4"));
+        assert_eq!("Nf3", san_for_position(DEFAULT_POSITION, "g1f3"));
+        // pawn capture and en passant
+        assert_eq!(
+            "exd5",
+            san_for_position("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5")
+        );
+        assert_eq!(
+            "exd6",
+            san_for_position("4k3/8/8/3pP3/8/8/8/4K3 w - d5 0 1", "e5d6")
+        );
+    }
+
+    #[test]
+    fn test_castle_and_promotion_san() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!("O-O", san_for_position(fen, "e1g1"));
+        assert_eq!("O-O-O", san_for_position(fen, "e1c1"));
+        assert_eq!(
+            "axb8=Q+",
+            san_for_position("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q")
+        );
+        assert_eq!(
+            "a8=R+",
+            san_for_position("2k5/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8r")
+        );
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        // two knights can reach d2
+        assert_eq!(
+            "Nbd2",
+            san_for_position("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1", "b1d2")
+        );
+        // two rooks on the same file
+        assert_eq!(
+            "R1a3",
+            san_for_position("R7/8/7k/8/8/8/8/R3K3 w - - 0 1", "a1a3")
+        );
+    }
+
+    #[test]
+    fn test_check_and_mate() {
+        assert_eq!(
+            "Qh4#",
+            san_for_position(
+                "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2",
+                "d8h4"
+            )
+        );
+        assert_eq!(
+            "Bb5+",
+            san_for_position(
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+                "f1b5"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sync_moves_keeps_comments() {
+        let mut game = PgnGame::default();
+        game.sync_moves(&["e2e4", "e7e5"]);
+        game.add_move(PgnMove::new(
+            "g1f3",
+            Some(MoveComment {
+                evaluation: 0.35,
+                depth: 12,
+                time: Duration::from_millis(1200),
+            }),
+        ));
+        game.sync_moves(&["e2e4", "e7e5", "g1f3", "b8c6"]);
+        assert_eq!(4, game.moves.len());
+        assert!(game.moves[2].comment.is_some());
+
+        // takeback - everything after the change is dropped
+        game.sync_moves(&["e2e4", "c7c5"]);
+        assert_eq!(2, game.moves.len());
+    }
+
+    #[test]
+    fn test_full_pgn() {
+        let mut game = PgnGame {
+            white: String::from("RustyRook"),
+            ..Default::default()
+        };
+        game.sync_moves(&["e2e4", "e7e5"]);
+        game.add_move(PgnMove::new(
+            "g1f3",
+            Some(MoveComment {
+                evaluation: 0.35,
+                depth: 12,
+                time: Duration::from_millis(1200),
+            }),
+        ));
+        game.set_expected_line(vec![String::from("g8f6"), String::from("f1c4")]);
+        game.sync_moves(&["e2e4", "e7e5", "g1f3", "b8c6"]);
+
+        let pgn = game.to_pgn();
+        assert!(pgn.starts_with("[Event \"?\"]\n"));
+        assert!(pgn.contains("[White \"RustyRook\"]\n"));
+        assert!(pgn.contains("[Result \"*\"]\n\n"));
+        assert!(!pgn.contains("[FEN"));
+        assert!(pgn.ends_with("1. e4 e5 2. Nf3 {+0.35/12 1.2s} 2... Nc6 (2... Nf6 3. Bc4) *\n"));
+    }
+
+    #[test]
+    fn test_expected_line_as_variation() {
+        let mut game = PgnGame::default();
+        game.sync_moves(&["e2e4"]);
+        game.add_move(PgnMove::new(
+            "e7e5",
+            Some(MoveComment {
+                evaluation: -0.2,
+                depth: 10,
+                time: Duration::from_millis(500),
+            }),
+        ));
+        game.set_expected_line(["g1f3", "b8c6"].map(String::from).to_vec());
+        // the expected reply is played - nothing to add
+        game.sync_moves(&["e2e4", "e7e5", "g1f3"]);
+        assert!(game.to_pgn().ends_with("1. e4 e5 {-0.20/10 0.5s} 2. Nf3 *\n"));
+        // another reply - the expected line is the alternative to it
+        game.sync_moves(&["e2e4", "e7e5", "d2d4"]);
+        assert!(game.to_pgn().ends_with("1. e4 e5 {-0.20/10 0.5s} 2. d4 (2. Nf3 Nc6) *\n"));
+    }
+
+    #[test]
+    fn test_pgn_from_fen() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 40";
+        let mut game = PgnGame::new(Some(String::from(fen)));
+        game.sync_moves(&["e8d7", "e2e4"]);
+
+        let pgn = game.to_pgn();
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]\n"));
+        assert!(pgn.ends_with("40... Kd7 41. e4 *\n"));
+    }
+}
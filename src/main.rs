@@ -1,36 +1,30 @@
 
-use crate::{engine::{sender::send_move, transposition::zobrist::{get_transposition_en_passant_numbers, get_transposition_figure_random_numbers}}, helper::{magic_bitboards::helper::init_king_safety_bitboards, moves_by_field::get_passed_pawn_rows, opening::{create_opening_map, OpeningMove}}};
+use crate::{engine::{sender::{convert_move_to_uci, send_move}, transposition::zobrist::{get_transposition_castle_numbers, get_transposition_current_move_number, get_transposition_en_passant_numbers, get_transposition_figure_random_numbers}}, helper::{magic_bitboards::helper::init_king_safety_bitboards, moves_by_field::get_passed_pawn_rows, opening::{create_opening_map, OpeningMove}, pgn::{MoveComment, PgnGame, PgnMove}}};
 use board::bitboard::Bitboard;
 use board::board::Chessboard;
 use dashmap::DashMap;
 use engine::{
-    count::count_moves,
-    engine::search_for_best_move,
-    transposition::{transposition::Transposition},
+    bench::run_bench,
+    perft::{perft, run_perft},
+    see::see,
+    engine::{get_late_move_reductions, search_for_best_move, SearchOptions, TimeForMove, MOVE_OVERHEAD},
+    transposition::table::TranspositionTable,
 };
 use figures::color::Color;
 use helper::{
-    magic_bitboards::{
-        init_with_predefined::{
-            init_bishop_magic_arrays, init_bishop_magic_moves_array, init_rook_magic_arrays,
-            init_rook_magic_moves_array,
-        },
-        magic_bitboard::MagicBitboard,
-    },
+    magic_bitboards::find::run_magic_search,
     moves_by_field::{
-        get_douplicate_pawn_boards, get_king_moves_for_field, get_knight_moves_for_field,
-        get_moves_for_each_field, get_pawn_promotion_moves, get_pawn_takes_for_field,
-        MoveInEveryDirection,
+        get_between_fields, get_douplicate_pawn_boards, get_king_moves_for_field, get_line_fields, get_knight_moves_for_field,
+        get_pawn_promotion_moves, get_pawn_takes_for_field,
     },
 };
 use lazy_static::lazy_static;
 use log::info;
 use once_cell::sync::Lazy;
 use rand::{distr::{weighted::WeightedIndex, Distribution}};
-use rustc_hash::FxHashMap;
 use simple_file_logger::init_logger;
 use std::{
-    io::{self}, time::SystemTime
+    io::{self}, time::{Duration, SystemTime}
 };
 mod board;
 mod engine;
@@ -38,7 +32,7 @@ mod evaluation;
 mod figures;
 mod helper;
 
-static TRANSPOSITION_TABLE: Lazy<DashMap<u64, Transposition>> = Lazy::new(||DashMap::with_capacity(3_200_000));
+static TRANSPOSITION_TABLE: Lazy<TranspositionTable> = Lazy::new(||DashMap::with_capacity(3_200_000));
 
 lazy_static! {
     static ref KNIGHT_MOVES: [Bitboard; 64] = {
@@ -55,25 +49,17 @@ lazy_static! {
     static ref PAWN_PROMOTION_FIELDS: Bitboard = {
         get_pawn_promotion_moves()
     };
-    // magic bitboards split into magic and possible moves for magic
-    static ref BISHOP_MAGIC_BITBOARDS: [MagicBitboard; 64] = {
-        init_bishop_magic_arrays()
-    };
     // relevant fields for king safety 
     static ref KING_SAFETY_FIELDS: [[Bitboard; 3]; 2] = {
         init_king_safety_bitboards()
     };
-    static ref BISHOP_MAGIC_POSITIONS: [Vec<Bitboard>; 64] = {
-        init_bishop_magic_moves_array()
-    };
-    static ref ROOK_MAGIC_BITBOARDS: [MagicBitboard; 64] = {
-        init_rook_magic_arrays()
+    // fields between two fields on the same line / diagonal
+    static ref BETWEEN_FIELDS: [[Bitboard; 64]; 64] = {
+        get_between_fields()
     };
-    static ref ROOK_MAGIC_POSITIONS: [Vec<Bitboard>; 64] = {
-        init_rook_magic_moves_array()
-    };
-    static ref MOVES_BY_FIELD: FxHashMap<usize, MoveInEveryDirection> = {
-        get_moves_for_each_field()
+    // complete line / diagonal through two fields
+    static ref LINE_FIELDS: [[Bitboard; 64]; 64] = {
+        get_line_fields()
     };
     static ref DOUPLICATE_PAWN_TARIFF: [Bitboard; 8] = {
         get_douplicate_pawn_boards()
@@ -81,27 +67,25 @@ lazy_static! {
     static ref PASSED_PAWN_ROWS: [Bitboard; 8] = {
         get_passed_pawn_rows()
     };
-    // static u64 to calculate zobrist hash for each color
+    // static u64 to calculate zobrist hash for each color (fixed polyglot layout)
     static ref ZOBRIST_FIGURE_NUMBERS: [[[u64; 64];6];2] = {
         get_transposition_figure_random_numbers()
     };
-    static ref ZOBRIST_SEED: u64 = {
-        3847293847293847239
-    };
+    // used if white is to move
     static ref ZOBRIST_CURRENT_MOVE: u64 = {
-        9182739182739182731
+        get_transposition_current_move_number()
     };
-    static ref ZOBRIST_EN_PASSANT: [u64; 64] = {
+    // by file
+    static ref ZOBRIST_EN_PASSANT: [u64; 8] = {
         get_transposition_en_passant_numbers()
     };
     // white short, white long - black short, black long
     static ref ZOBRIST_CASTLE_NUMBERS: [u64;4] = {
-        [
-            13294823984729384712,
-            8473928472384729384,
-            1923847192384719238,
-            982374928374928374
-        ]
+        get_transposition_castle_numbers()
+    };
+    // plies late quiet moves are reduced by - by remaining depth and number of the move
+    static ref LATE_MOVE_REDUCTIONS: [[u8; 64]; 64] = {
+        get_late_move_reductions()
     };
     // Openings Book
     static ref OPENINGS: DashMap<u64, Vec<OpeningMove>> = {
@@ -111,6 +95,16 @@ lazy_static! {
 
 
 fn main() {
+    // RustyRook perft <depth> [hash <mb>] [fen <fen> | epd <file>], RustyRook magics [rook | bishop] [reduce <bits>]
+    // or RustyRook bench [time <ms>] [epd <file>] [recapture] [pawnpush]
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let commands: Vec<&str> = args.iter().map(String::as_str).collect();
+    match commands.first() {
+        Some(&"perft") => return run_perft(&commands, &Chessboard{..Default::default()}),
+        Some(&"magics") => return run_magic_search(&commands),
+        Some(&"bench") => return run_bench(&commands),
+        _ => {}
+    }
     init_logger!("rustyRook").unwrap();
     parse_input();
 }
@@ -120,15 +114,22 @@ fn map_input_to_action(
     chessboard: &mut Chessboard,
     once_played_positions: &mut Vec<u64>,
     twice_played_positions: &mut Vec<u64>,
+    game: &mut PgnGame,
+    options: &mut SearchOptions,
 ) {
     let differentiation: &str = commands.first().unwrap_or(&"stop");
     match differentiation {
         "uci" => send_uci_message(),
         "isready" => send_is_ready(),
-        "ucinewgame" => init_new_game(once_played_positions, twice_played_positions),
-        "position" => update_board(commands, chessboard, once_played_positions, twice_played_positions),
-        "go" => make_move(commands, chessboard, twice_played_positions),
+        "setoption" => set_option(&commands, options),
+        "ucinewgame" => init_new_game(once_played_positions, twice_played_positions, game),
+        "position" => update_board(commands, chessboard, once_played_positions, twice_played_positions, game),
+        "go" => make_move_and_log_game(commands, chessboard, twice_played_positions, game, *options),
         "debug" => debug_moves(chessboard),
+        "perft" => run_perft(&commands, chessboard),
+        "see" => print_see(&commands, chessboard),
+        "magics" => run_magic_search(&commands),
+        "bench" => run_bench(&commands),
         "quit" => quit(String::from("Ending Game")),
         _ => quit(String::from("Unknown Command!")),
     }
@@ -137,7 +138,7 @@ fn map_input_to_action(
 fn debug_moves(chessboard: &Chessboard) {
     let now = SystemTime::now();
     let max_depth: u8 = 4;
-    let moves = count_moves(chessboard, max_depth);
+    let moves = perft(chessboard, max_depth, None);
     println!(
         "Moves: {} - Depth: {} - took: {:?}",
         moves,
@@ -146,97 +147,133 @@ fn debug_moves(chessboard: &Chessboard) {
     );
 }
 
+// see <move> - material won or lost by the exchange started with the move
+fn print_see(commands: &[&str], chessboard: &Chessboard) {
+    match commands.get(1).and_then(|mov| chessboard.get_move_from_uci_input(mov)) {
+        Some(mov) => println!("see {}: {}", convert_move_to_uci(mov), see(chessboard, mov)),
+        None => println!("usage: see <move>"),
+    }
+}
+
 fn update_board(
     move_vec: Vec<&str>, 
     board: &mut Chessboard,
     once_played_positions: &mut Vec<u64>, 
-    twice_played_positions: &mut Vec<u64>) {
+    twice_played_positions: &mut Vec<u64>,
+    game: &mut PgnGame) {
     // not beautiful - but also not really important for performance
-    board.set_to_default();
+    let start_position = get_start_position_from_commands(&move_vec);
+    match &start_position {
+        Some(fen) => board.create_position_from_input_string(fen.clone()),
+        None => board.set_to_default(),
+    }
     once_played_positions.clear();
     twice_played_positions.clear();
-    for single_move in move_vec {
-        // ignore both for now - should not be needed as ucinewgame resets game
-        if single_move != "position" && single_move != "startpos" && single_move != "moves" {
-            board.update_position_from_uci_input(single_move);
-            
-            // performance does not matter for these few moves
-            if !once_played_positions.contains(&board.zobrist_key){
-                once_played_positions.push(board.zobrist_key);
-            }else if !twice_played_positions.contains(&board.zobrist_key){
-                twice_played_positions.push(board.zobrist_key);
-            }
+    if game.start_position != start_position {
+        *game = PgnGame::new(start_position);
+    }
+    let played_moves: Vec<&str> = move_vec.iter().skip_while(|x| **x != "moves").skip(1).copied().collect();
+    game.sync_moves(&played_moves);
+    for single_move in played_moves {
+        board.update_position_from_uci_input(single_move);
+
+        // performance does not matter for these few moves
+        if !once_played_positions.contains(&board.zobrist_key){
+            once_played_positions.push(board.zobrist_key);
+        }else if !twice_played_positions.contains(&board.zobrist_key){
+            twice_played_positions.push(board.zobrist_key);
         }
     }
 }
 
-fn make_move(commands:  Vec<&str>, board: &Chessboard, twice_played_positions: &[u64]) {
+// position fen <fen> moves ... - None for startpos
+fn get_start_position_from_commands(commands: &[&str]) -> Option<String> {
+    if commands.get(1) != Some(&"fen") {
+        return None;
+    }
+    let fen: Vec<&str> = commands.iter().skip(2).take_while(|x| **x != "moves").copied().collect();
+    Some(fen.join(" "))
+}
+
+// log the whole game as PGN after each move, so we are able to replay what was played
+// the rest of the pv is the line we expect - it becomes a variation if the opponent plays another move
+fn make_move_and_log_game(
+    commands: Vec<&str>,
+    board: &Chessboard,
+    twice_played_positions: &[u64],
+    game: &mut PgnGame,
+    options: SearchOptions,
+) {
+    if let Some((played_move, pv)) = make_move(commands, board, twice_played_positions, options) {
+        game.add_move(played_move);
+        game.set_expected_line(pv.into_iter().skip(1).collect());
+        info!("Current Game:\n{}", game.to_pgn());
+    }
+}
+
+fn make_move(
+    commands: Vec<&str>,
+    board: &Chessboard,
+    twice_played_positions: &[u64],
+    options: SearchOptions,
+) -> Option<(PgnMove, Vec<String>)> {
     // we are still in our opening
     if OPENINGS.contains_key(&board.zobrist_key){
         info!("Playing move from Opening Book");
-        play_opening(board);
-        return;
+        return play_opening(board);
     }
+    let now = SystemTime::now();
     let time_for_move = get_time_for_move(commands, board.current_move);
     let possible_repetition = !twice_played_positions.is_empty();
-    search_for_best_move(time_for_move, board, possible_repetition, twice_played_positions);
+    let (best_move, evaluation, depth, pv) = search_for_best_move(time_for_move, board, possible_repetition, twice_played_positions, options);
+    let comment = MoveComment {
+        evaluation,
+        depth,
+        time: now.elapsed().unwrap_or(Duration::new(0, 0)),
+    };
+    let pv = pv.into_iter().map(convert_move_to_uci).collect();
+    Some((PgnMove::new(&convert_move_to_uci(best_move), Some(comment)), pv))
 }
 
 
-fn play_opening(board: &Chessboard){
+fn play_opening(board: &Chessboard) -> Option<(PgnMove, Vec<String>)>{
     if let Some(options) = OPENINGS.get(&board.zobrist_key){
         let mut rng = rand::rng(); 
         // play moves based on play count
         let weights: Vec<u32> = options.iter().map(|x| x.count).collect();
         let dist = WeightedIndex::new(&weights).unwrap();
         let move_to_play = options[dist.sample(&mut rng)];
-        send_move(move_to_play.from, move_to_play.to, None);
-        return;
+        send_move(move_to_play.mov, None);
+        return Some((PgnMove::new(&convert_move_to_uci(move_to_play.mov), None), Vec::new()));
     }
+    None
 }
 
-fn get_time_for_move(commands:  Vec<&str>, color: Color) -> u64{
+fn get_time_for_move(commands:  Vec<&str>, color: Color) -> TimeForMove{
     match color{
         Color::White => get_time(commands, "wtime", "winc"),
         Color::Black => get_time(commands, "btime", "binc")
     }
 }
 
-fn get_time(commands:  Vec<&str>, overall_time_key: &str, increment_key: &str ) -> u64{
-    let mut user_time: u64 = 0;
-
+fn get_time(commands:  Vec<&str>, overall_time_key: &str, increment_key: &str ) -> TimeForMove{
     // given a exact time per move
     let exact_movetime_opt = get_value_from_commands(&commands, "movetime");
     if let Some(exact_movetime) = exact_movetime_opt{
-        return exact_movetime - 100; // buffer to send and finish calculation
+        return TimeForMove::fixed(exact_movetime.saturating_sub(MOVE_OVERHEAD)); // buffer to send and finish calculation
     }
 
     let given_time_opt = get_value_from_commands(&commands, overall_time_key);
     // no timelimit -> we take 10s to calculate
     if given_time_opt.is_none(){
-        return 10000;
+        return TimeForMove::fixed(10000);
     }
     let given_time = given_time_opt.unwrap();
-    
-    // if there is an increment calculate average from rest time and add it to time
-    let moves_until_increment_opt = get_value_from_commands(&commands, "movestogo");
-    if let Some(move_until_increment) = moves_until_increment_opt{
-        user_time += given_time / (move_until_increment +2) // +2 to add some buffer for overhead
-    }else{
-        user_time += given_time / 40 // just make some guess on total count of moves to manage time
-    }
-
-    // add by move increment to each calculation
-    let increment_opt = get_value_from_commands(&commands, increment_key);
-    if let Some(increment) = increment_opt{
-        user_time +=increment;
-    }
-    
-    if user_time > 15000{
-        // max take 15s, so we dont calculate forever
-        return 15000 
-    }
-    user_time
+    TimeForMove::from_clock(
+        given_time,
+        get_value_from_commands(&commands, "movestogo"),
+        get_value_from_commands(&commands, increment_key),
+    )
 }
 
 
@@ -244,9 +281,8 @@ fn get_value_from_commands(commands:  &Vec<&str>, key: &str) -> Option<u64>{
     let increment_index_opt = commands.iter().position(|x| x.eq(&key));
     if let Some(increment_index) = increment_index_opt{
         if let Some(increment) = commands.get(increment_index+1){
-            let value_result = increment.parse();
-            if value_result.is_ok(){
-                return Some(value_result.unwrap());
+            if let Ok(value) = increment.parse(){
+                return Some(value);
             }
         }
     }
@@ -257,10 +293,11 @@ fn quit(message: String) {
     panic!("{}", message);
 }
 
-fn init_new_game(once_played_positions: &mut Vec<u64>, twice_played_positions: &mut Vec<u64>) {
+fn init_new_game(once_played_positions: &mut Vec<u64>, twice_played_positions: &mut Vec<u64>, game: &mut PgnGame) {
     // cleanup and init of static values
     once_played_positions.clear();
     twice_played_positions.clear();
+    *game = PgnGame::default();
     TRANSPOSITION_TABLE.clear();
     init_static_values();
     println!("isready");
@@ -272,9 +309,22 @@ fn send_is_ready() {
 fn send_uci_message() {
     println!("id name RustyRook");
     println!("id author Benjamin Zenth");
+    println!("option name RecaptureExtension type check default false");
+    println!("option name PawnPushExtension type check default false");
     println!("uciok");
 }
 
+// setoption name <name> value <true | false>
+fn set_option(commands: &[&str], options: &mut SearchOptions) {
+    let name = commands.iter().skip_while(|x| **x != "name").nth(1);
+    let value = commands.iter().skip_while(|x| **x != "value").nth(1) == Some(&"true");
+    match name {
+        Some(&"RecaptureExtension") => options.recapture_extension = value,
+        Some(&"PawnPushExtension") => options.pawn_push_extension = value,
+        _ => println!("info string unknown option"),
+    }
+}
+
 fn init_static_values(){
     let _ = KING_MOVES.first();
     let _ = KNIGHT_MOVES.first();
@@ -284,9 +334,7 @@ fn init_static_values(){
     let _ = PASSED_PAWN_ROWS.first();
     let _ = ZOBRIST_FIGURE_NUMBERS.first();
     let _ = KING_SAFETY_FIELDS.first();
-    // positions are based on magic and impl. init magics
-    let _ = BISHOP_MAGIC_POSITIONS[0];
-    let _ = ROOK_MAGIC_POSITIONS[0];
+    let _ = LATE_MOVE_REDUCTIONS.first();
     let _ = OPENINGS.get(&1);
 }
 
@@ -298,11 +346,20 @@ fn parse_input() -> String {
     // Repetition
     let mut once_played_positions: Vec<u64> = Vec::new();
     let mut twice_played_positions: Vec<u64> = Vec::new();
+    let mut game = PgnGame::default();
+    let mut options = SearchOptions::default();
     loop {
         let mut buffer_string = String::new();
         io::stdin().read_line(&mut buffer_string).ok().unwrap();
         info!("Recieved Message: {buffer_string}");
         let commands: Vec<&str> = buffer_string.split_whitespace().collect();
-        map_input_to_action(commands, &mut chessboard, &mut once_played_positions, &mut twice_played_positions);
+        map_input_to_action(
+            commands,
+            &mut chessboard,
+            &mut once_played_positions,
+            &mut twice_played_positions,
+            &mut game,
+            &mut options,
+        );
     }
 }